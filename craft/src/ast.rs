@@ -1,14 +1,20 @@
+#[cfg(feature = "bigint")]
+use crate::bigint::BigInt;
+use crate::native::NativeFunction;
 use crate::token::Token;
+use std::fmt::Display;
+use std::rc::Rc;
 
 /// Generates a AST node struct with the given name and fields.
 /// Example:
-/// ```
+/// ```ignore
 /// generate_node!(Node, field1: Type1, field2: Type2);
 /// ```
 macro_rules! generate_node {
     ($name:ident, $($field:ident : $type:ty),*) => {
 
         #[derive(Debug, Clone)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         pub struct $name {
             $(pub $field: $type),*
         }
@@ -24,47 +30,99 @@ macro_rules! generate_node {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Expr {
+    Assign(Box<Assign>),
     Binary(Box<Binary>),
+    Call(Box<Call>),
+    Comma(Box<Comma>),
+    Conditional(Box<Conditional>),
     Grouping(Box<Grouping>),
+    Interpolation(Box<Interpolation>),
     Literal(Box<Literal>),
     Unary(Box<Unary>),
+    Variable(Box<Variable>),
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Object {
     Number(f64),
+    /// A 64-bit integer. Integer literals have no sign, so the smallest value can't be written as
+    /// one: `-9223372036854775808` negates a literal that's too large, and is written
+    /// `-9223372036854775807 - 1` instead.
+    Int(i64),
+    /// An integer beyond the range of `Int`, which is used for every integer that fits.
+    #[cfg(feature = "bigint")]
+    BigInt(BigInt),
     String(String),
     Identifier(String),
     Boolean(bool),
+    /// Functions implemented in Rust can't be serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    NativeFunction(Rc<NativeFunction>),
     Nil,
 }
 
+impl Display for Object {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Object::Nil => write!(f, "nil"),
+            Object::Boolean(value) => write!(f, "{}", value),
+            Object::Number(value) => write!(f, "{}", value),
+            Object::Int(value) => write!(f, "{}", value),
+            #[cfg(feature = "bigint")]
+            Object::BigInt(value) => write!(f, "{}", value),
+            Object::String(value) => write!(f, "{}", value),
+            Object::Identifier(value) => write!(f, "Identifier '{}'", value),
+            Object::NativeFunction(value) => write!(f, "{}", value),
+        }
+    }
+}
+
 pub trait Visitor<T> {
     fn visit_expr(&mut self, expr: &Expr) -> T;
+    fn visit_assign_expr(&mut self, expr: &Assign) -> T;
     fn visit_binary_expr(&mut self, expr: &Binary) -> T;
+    fn visit_call_expr(&mut self, expr: &Call) -> T;
+    fn visit_comma_expr(&mut self, expr: &Comma) -> T;
+    fn visit_conditional_expr(&mut self, expr: &Conditional) -> T;
     fn visit_grouping_expr(&mut self, expr: &Grouping) -> T;
+    fn visit_interpolation_expr(&mut self, expr: &Interpolation) -> T;
     fn visit_literal_expr(&mut self, expr: &Literal) -> T;
     fn visit_unary_expr(&mut self, expr: &Unary) -> T;
+    fn visit_variable_expr(&mut self, expr: &Variable) -> T;
 }
 
 pub trait StmtVisitor<T> {
     fn visit_stmt(&mut self, stmt: &Stmt) -> T;
     fn visit_expression_stmt(&mut self, stmt: &Expression) -> T;
     fn visit_print_stmt(&mut self, stmt: &Print) -> T;
+    fn visit_var_stmt(&mut self, stmt: &Var) -> T;
 }
 
+generate_node!(Assign, name: Token, value: Expr);
 generate_node!(Binary, left: Expr, operator: Token, right: Expr);
+generate_node!(Call, callee: Expr, paren: Token, arguments: Vec<Expr>);
+generate_node!(Comma, left: Expr, right: Expr);
+generate_node!(Conditional, condition: Expr, then_branch: Expr, else_branch: Expr);
 generate_node!(Grouping, expression: Expr);
+// The parts of an interpolated string alternate between string literals for its segments and the
+// expressions between them, starting and ending with a segment.
+generate_node!(Interpolation, parts: Vec<Expr>);
 generate_node!(Literal, value: Object);
 generate_node!(Unary, operator: Token, right: Expr);
+generate_node!(Variable, name: Token);
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Stmt {
     Expression(Box<Expression>),
     Print(Box<Print>),
+    Var(Box<Var>),
     Empty,
 }
 
 generate_node!(Expression, expr: Expr);
 generate_node!(Print, expr: Expr);
+generate_node!(Var, name: Token, initializer: Option<Expr>);
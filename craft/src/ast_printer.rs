@@ -24,35 +24,118 @@ pub fn print_expr(expr: Expr) -> String {
     AstPrinter {}.visit_expr(&expr)
 }
 
+/// Returns a string representation of the given statement.
+pub fn print_stmt(stmt: &Stmt) -> String {
+    AstPrinter {}.visit_stmt(stmt)
+}
+
 impl Visitor<String> for AstPrinter {
     fn visit_expr(&mut self, expr: &Expr) -> String {
         match expr {
+            Expr::Assign(expr) => self.visit_assign_expr(expr),
             Expr::Binary(expr) => self.visit_binary_expr(expr),
+            Expr::Call(expr) => self.visit_call_expr(expr),
+            Expr::Comma(expr) => self.visit_comma_expr(expr),
+            Expr::Conditional(expr) => self.visit_conditional_expr(expr),
             Expr::Grouping(expr) => self.visit_grouping_expr(expr),
+            Expr::Interpolation(expr) => self.visit_interpolation_expr(expr),
             Expr::Literal(expr) => self.visit_literal_expr(expr),
             Expr::Unary(expr) => self.visit_unary_expr(expr),
+            Expr::Variable(expr) => self.visit_variable_expr(expr),
         }
     }
 
+    fn visit_assign_expr(&mut self, expr: &Assign) -> String {
+        self.parenthesize(&format!("= {}", expr.name.lexeme), vec![&expr.value])
+    }
+
     fn visit_binary_expr(&mut self, expr: &Binary) -> String {
         self.parenthesize(&expr.operator.lexeme, vec![&expr.left, &expr.right])
     }
 
+    fn visit_call_expr(&mut self, expr: &Call) -> String {
+        let mut exprs = vec![&expr.callee];
+        exprs.extend(&expr.arguments);
+        self.parenthesize("call", exprs)
+    }
+
+    fn visit_comma_expr(&mut self, expr: &Comma) -> String {
+        self.parenthesize(",", vec![&expr.left, &expr.right])
+    }
+
+    fn visit_conditional_expr(&mut self, expr: &Conditional) -> String {
+        self.parenthesize(
+            "?:",
+            vec![&expr.condition, &expr.then_branch, &expr.else_branch],
+        )
+    }
+
     fn visit_grouping_expr(&mut self, expr: &Grouping) -> String {
         self.parenthesize("group", vec![&expr.expression])
     }
 
+    fn visit_interpolation_expr(&mut self, expr: &Interpolation) -> String {
+        // Leave out empty segments, such as the ones around an interpolation that fills a string.
+        let parts = expr
+            .parts
+            .iter()
+            .filter(|part| match part {
+                Expr::Literal(literal) => {
+                    !matches!(&literal.value, Object::String(segment) if segment.is_empty())
+                }
+                _ => true,
+            })
+            .collect();
+        self.parenthesize("interpolate", parts)
+    }
+
     fn visit_literal_expr(&mut self, expr: &Literal) -> String {
         match &expr.value {
             Object::Nil => "nil".to_string(),
             Object::Boolean(value) => value.to_string(),
             Object::Number(value) => value.to_string(),
+            Object::Int(value) => value.to_string(),
+            #[cfg(feature = "bigint")]
+            Object::BigInt(value) => value.to_string(),
             Object::String(value) => value.to_string(),
             Object::Identifier(value) => value.to_string(),
+            Object::NativeFunction(value) => value.to_string(),
         }
     }
 
     fn visit_unary_expr(&mut self, expr: &Unary) -> String {
         self.parenthesize(&expr.operator.lexeme, vec![&expr.right])
     }
+
+    fn visit_variable_expr(&mut self, expr: &Variable) -> String {
+        expr.name.lexeme.clone()
+    }
+}
+
+impl StmtVisitor<String> for AstPrinter {
+    fn visit_stmt(&mut self, stmt: &Stmt) -> String {
+        match stmt {
+            Stmt::Expression(stmt) => self.visit_expression_stmt(stmt),
+            Stmt::Print(stmt) => self.visit_print_stmt(stmt),
+            Stmt::Var(stmt) => self.visit_var_stmt(stmt),
+            Stmt::Empty => String::new(),
+        }
+    }
+
+    fn visit_expression_stmt(&mut self, stmt: &Expression) -> String {
+        self.parenthesize(";", vec![&stmt.expr])
+    }
+
+    fn visit_print_stmt(&mut self, stmt: &Print) -> String {
+        self.parenthesize("print", vec![&stmt.expr])
+    }
+
+    fn visit_var_stmt(&mut self, stmt: &Var) -> String {
+        match &stmt.initializer {
+            Some(initializer) => {
+                self.parenthesize(&format!("var {} =", stmt.name.lexeme), vec![initializer])
+            }
+            None => format!("(var {})", stmt.name.lexeme),
+        }
+    }
 }
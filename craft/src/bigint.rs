@@ -0,0 +1,115 @@
+use crate::ast::Object;
+use crate::interpreter::{RuntimeError, RuntimeResult};
+use crate::token::{Token, TokenType};
+pub use num_bigint::BigInt;
+use num_traits::{FromPrimitive, One, Signed, ToPrimitive, Zero};
+
+/// The most bits the result of a shift or a power can have, so that a single operation can't use
+/// up the interpreter's memory. This is 8 MiB of digits.
+const MAX_BITS: u64 = 1 << 26;
+
+/// Returns the integer as an [`Object::Int`] if it fits in 64 bits, so that each integer has a
+/// single representation whether or not it went through big integer arithmetic.
+pub fn normalize(value: BigInt) -> Object {
+    match i64::try_from(&value) {
+        Ok(value) => Object::Int(value),
+        Err(_) => Object::BigInt(value),
+    }
+}
+
+/// Returns the value as a big integer, if it's an integer or a float with a whole value.
+pub fn from_object(value: &Object) -> Option<BigInt> {
+    match value {
+        Object::Int(value) => Some(BigInt::from(*value)),
+        Object::BigInt(value) => Some(value.clone()),
+        Object::Number(value) if value.fract() == 0.0 => BigInt::from_f64(*value),
+        _ => None,
+    }
+}
+
+/// Returns the nearest float, which is infinite for integers beyond the range of `f64`.
+pub fn to_f64(value: &BigInt) -> f64 {
+    value.to_f64().unwrap_or(if value.is_negative() {
+        f64::NEG_INFINITY
+    } else {
+        f64::INFINITY
+    })
+}
+
+/// Evaluates an arithmetic or comparison operator on two integers, with the same results as for
+/// 64-bit integers where those don't overflow.
+pub fn arithmetic(operator: &Token, left: &BigInt, right: &BigInt) -> RuntimeResult<Object> {
+    let value = match operator.variant {
+        TokenType::PLUS => left + right,
+        TokenType::MINUS => left - right,
+        TokenType::STAR => left * right,
+        TokenType::SLASH => return Ok(Object::Number(to_f64(left) / to_f64(right))),
+        TokenType::PERCENT if right.is_zero() => {
+            return Err(RuntimeError::new(operator, "Modulo by zero."));
+        }
+        TokenType::PERCENT => left % right,
+        TokenType::TILDE_SLASH if right.is_zero() => {
+            return Err(RuntimeError::new(operator, "Integer division by zero."));
+        }
+        TokenType::TILDE_SLASH => left / right,
+        TokenType::STAR_STAR if right.is_negative() => {
+            return Ok(Object::Number(to_f64(left).powf(to_f64(right))));
+        }
+        TokenType::STAR_STAR => match right
+            .to_u32()
+            .filter(|exponent| left.bits().saturating_mul(u64::from(*exponent)) <= MAX_BITS)
+        {
+            Some(exponent) => left.pow(exponent),
+            // Only the powers of 0, 1 and -1 stay small for exponents this large.
+            None if left.is_zero() || left.is_one() => left.clone(),
+            None if left.magnitude().is_one() && right.bit(0) => left.clone(),
+            None if left.magnitude().is_one() => BigInt::one(),
+            None => return Err(RuntimeError::new(operator, "Exponent is too large.")),
+        },
+        TokenType::GREATER => return Ok(Object::Boolean(left > right)),
+        TokenType::GREATER_EQUAL => return Ok(Object::Boolean(left >= right)),
+        TokenType::LESS => return Ok(Object::Boolean(left < right)),
+        TokenType::LESS_EQUAL => return Ok(Object::Boolean(left <= right)),
+        TokenType::BANG_EQUAL => return Ok(Object::Boolean(left != right)),
+        TokenType::EQUAL_EQUAL => return Ok(Object::Boolean(left == right)),
+        // The parser only builds binary expressions with the operators above, but a syntax tree
+        // loaded from JSON can have any token.
+        _ => {
+            return Err(RuntimeError::new(
+                operator,
+                "Invalid operator for integers.",
+            ))
+        }
+    };
+
+    Ok(normalize(value))
+}
+
+/// Evaluates a bitwise or shift operator, treating negative integers as two's complement like
+/// 64-bit integers are.
+pub fn bitwise(operator: &Token, left: &BigInt, right: &BigInt) -> RuntimeResult<Object> {
+    let value = match operator.variant {
+        TokenType::AMPERSAND => left & right,
+        TokenType::PIPE => left | right,
+        TokenType::CARET => left ^ right,
+        TokenType::LESS_LESS | TokenType::GREATER_GREATER if right.is_negative() => {
+            return Err(RuntimeError::new(
+                operator,
+                "Shift amount must not be negative.",
+            ));
+        }
+        TokenType::LESS_LESS if left.is_zero() => BigInt::zero(),
+        TokenType::LESS_LESS => {
+            let shift = u64::try_from(right)
+                .ok()
+                .filter(|shift| left.bits().saturating_add(*shift) <= MAX_BITS)
+                .ok_or_else(|| RuntimeError::new(operator, "Shift amount is too large."))?;
+            left << shift
+        }
+        // Shifting right by more bits than the integer has leaves only its sign.
+        TokenType::GREATER_GREATER => left >> usize::try_from(right).unwrap_or(usize::MAX),
+        _ => unreachable!("Not a bitwise operator: {:?}", operator),
+    };
+
+    Ok(normalize(value))
+}
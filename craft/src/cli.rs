@@ -1,4 +1,5 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use craft::Dump;
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -6,13 +7,76 @@ use std::path::PathBuf;
     author,
     version,
     about,
-    long_about = "My fun programming language interpreter"
+    long_about = "My fun programming language interpreter",
+    args_conflicts_with_subcommands = true
 )]
 pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     #[arg(short, long, help = "Input file to use")]
     pub file: Option<PathBuf>,
+
+    #[arg(
+        long,
+        group = "dump",
+        requires = "file",
+        help = "Print the tokens of the input file instead of running it"
+    )]
+    pub tokens: bool,
+
+    #[arg(
+        long,
+        group = "dump",
+        requires = "file",
+        help = "Print the syntax tree of the input file instead of running it"
+    )]
+    pub ast: bool,
+
+    #[arg(
+        long,
+        requires = "dump",
+        help = "Print --tokens or --ast output as JSON"
+    )]
+    pub json: bool,
+
+    #[arg(
+        long,
+        requires = "file",
+        conflicts_with = "dump",
+        help = "Run a syntax tree saved with --ast --json instead of source code"
+    )]
+    pub from_ast: bool,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Format Lox files in place
+    Fmt {
+        #[arg(
+            long,
+            help = "List the files that aren't formatted instead of changing them, and fail if there are any"
+        )]
+        check: bool,
+
+        #[arg(required = true, help = "Files to format")]
+        files: Vec<PathBuf>,
+    },
+}
+
+impl Cli {
+    /// Returns what to print instead of running the input file, if anything.
+    pub fn dump(&self) -> Option<Dump> {
+        if self.tokens {
+            Some(Dump::Tokens)
+        } else if self.ast {
+            Some(Dump::Ast)
+        } else {
+            None
+        }
+    }
 }
 
-pub fn parse_args() -> Cli {
-    Cli::parse()
+pub fn parse_args() -> Result<Cli, clap::Error> {
+    Cli::try_parse()
 }
@@ -0,0 +1,227 @@
+use crate::ast::{Object, Stmt};
+use crate::environment::Environment;
+use crate::formatter;
+use crate::interpreter::{Interpreter, RuntimeError};
+use crate::parser::Parser;
+use crate::scanner::Scanner;
+use crate::take_diagnostics;
+use crate::token::{Token, TokenType};
+use log::*;
+use std::fmt::Display;
+use std::io::Write;
+
+/// An error produced while evaluating source code with an [`Engine`].
+#[derive(Debug)]
+pub enum Error {
+    /// The source could not be scanned or parsed. Holds one message per reported problem.
+    Compile(Vec<String>),
+    /// The program failed while executing.
+    Runtime(RuntimeError),
+    /// No global with the given name exists.
+    UndefinedGlobal(String),
+    /// The program's output could not be written.
+    Io(std::io::Error),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Compile(messages) => write!(f, "{}", messages.join("\n")),
+            Error::Runtime(err) => write!(f, "{}", err),
+            Error::UndefinedGlobal(name) => write!(f, "Undefined variable '{}'.", name),
+            Error::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<RuntimeError> for Error {
+    fn from(err: RuntimeError) -> Self {
+        Error::Runtime(err)
+    }
+}
+
+/// A long-lived interpreter session for host applications.
+///
+/// State such as global variables survives between calls to [`Engine::eval`].
+pub struct Engine {
+    interpreter: Interpreter,
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Engine {
+    pub fn new() -> Self {
+        Self {
+            interpreter: Interpreter::new(),
+        }
+    }
+
+    /// Redirects the output of `print` statements.
+    pub fn set_stdout(&mut self, stdout: impl Write + 'static) {
+        self.interpreter.set_stdout(Box::new(stdout));
+    }
+
+    /// Redirects compile and runtime error messages.
+    pub fn set_stderr(&mut self, stderr: impl Write + 'static) {
+        self.interpreter.set_stderr(Box::new(stderr));
+    }
+
+    /// Runs the given source code. Returns the value of the last statement if it is an expression
+    /// statement, and `nil` otherwise.
+    pub fn eval(&mut self, source: &str) -> Result<Object, Error> {
+        let stmts = self.parse(source)?;
+        self.execute(&stmts)
+    }
+
+    /// Runs a line of interactive input. Unlike [`Engine::eval`], the input may end with an
+    /// expression without a semicolon, whose value is printed.
+    pub fn eval_repl(&mut self, source: &str) -> Result<Object, Error> {
+        let stmts = self.parse_repl(source)?;
+        self.execute(&stmts)
+    }
+
+    /// Runs already parsed statements, for example a syntax tree loaded from JSON. Returns the
+    /// value of the last statement like [`Engine::eval`].
+    pub fn execute(&mut self, stmts: &[Stmt]) -> Result<Object, Error> {
+        let mut value = Object::Nil;
+        for stmt in stmts {
+            let result = match stmt {
+                // The parser emits an empty statement for the end of input.
+                Stmt::Empty => continue,
+                Stmt::Expression(stmt) => self.interpreter.evaluate(&stmt.expr),
+                stmt => self.interpreter.execute(stmt).map(|_| Object::Nil),
+            };
+
+            value = result.map_err(|err| self.report_runtime_error(err))?;
+        }
+
+        Ok(value)
+    }
+
+    /// Scans and parses the given source code, reporting any errors.
+    pub fn parse(&mut self, source: &str) -> Result<Vec<Stmt>, Error> {
+        self.parse_with(source, Parser::new)
+    }
+
+    /// Parses interactive input, see [`Engine::eval_repl`].
+    pub fn parse_repl(&mut self, source: &str) -> Result<Vec<Stmt>, Error> {
+        self.parse_with(source, Parser::new_repl)
+    }
+
+    fn parse_with(
+        &mut self,
+        source: &str,
+        new_parser: fn(Vec<Token>) -> Parser,
+    ) -> Result<Vec<Stmt>, Error> {
+        // Discard anything left over by a previous run on this thread.
+        take_diagnostics();
+
+        let tokens = self.scan_tokens(source);
+        let stmts = new_parser(tokens).parse();
+
+        self.report_diagnostics()?;
+        Ok(stmts)
+    }
+
+    /// Scans the given source code into tokens, reporting any errors.
+    pub fn scan(&mut self, source: &str) -> Result<Vec<Token>, Error> {
+        take_diagnostics();
+        let tokens = self.scan_tokens(source);
+        self.report_diagnostics()?;
+        Ok(tokens)
+    }
+
+    /// Returns the given source code formatted, reporting any errors. Code that doesn't parse is
+    /// not formatted.
+    pub fn format(&mut self, source: &str) -> Result<String, Error> {
+        take_diagnostics();
+
+        let tokens = Scanner::with_trivia(source.to_string()).scan_tokens();
+        // Check that the code parses, leaving out the comments.
+        let code = tokens
+            .iter()
+            .filter(|token| token.variant != TokenType::COMMENT)
+            .cloned()
+            .collect();
+        Parser::new(code).parse();
+        self.report_diagnostics()?;
+
+        Ok(formatter::format_tokens(&tokens))
+    }
+
+    fn scan_tokens(&mut self, source: &str) -> Vec<Token> {
+        let mut scanner = Scanner::new(source.to_string());
+        let tokens = scanner.scan_tokens();
+
+        for token in &tokens {
+            debug!("{token}");
+        }
+
+        tokens
+    }
+
+    /// Reports the compile errors collected since the last call, if there are any.
+    fn report_diagnostics(&mut self) -> Result<(), Error> {
+        let diagnostics = take_diagnostics();
+        if diagnostics.is_empty() {
+            return Ok(());
+        }
+
+        for diagnostic in &diagnostics {
+            self.interpreter.report(diagnostic);
+        }
+        Err(Error::Compile(diagnostics))
+    }
+
+    /// Binds a global variable, replacing any existing value.
+    pub fn set_global(&mut self, name: &str, value: Object) {
+        self.interpreter.globals_mut().define(name, value);
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<Object> {
+        self.interpreter.globals().value(name)
+    }
+
+    pub fn globals(&self) -> &Environment {
+        self.interpreter.globals()
+    }
+
+    /// Registers a Rust closure as a global function callable from Lox code.
+    pub fn define_native(
+        &mut self,
+        name: &str,
+        arity: usize,
+        function: impl Fn(&[Object]) -> Result<Object, RuntimeError> + 'static,
+    ) {
+        self.interpreter.define_native(name, arity, function);
+    }
+
+    /// Calls the global function with the given name.
+    pub fn call_function(&mut self, name: &str, arguments: &[Object]) -> Result<Object, Error> {
+        let callee = self
+            .get_global(name)
+            .ok_or_else(|| Error::UndefinedGlobal(name.to_string()))?;
+
+        self.interpreter
+            .call(&callee, arguments)
+            .map_err(|err| self.report_runtime_error(err))
+    }
+
+    fn report_runtime_error(&mut self, err: RuntimeError) -> Error {
+        // A failed write isn't a bug in the program, so it's reported without a line.
+        if let Some(err) = self.interpreter.take_output_error() {
+            let err = Error::Io(err);
+            self.interpreter.report(&err);
+            return err;
+        }
+
+        self.interpreter.runtime_error(&err);
+        Error::Runtime(err)
+    }
+}
@@ -0,0 +1,49 @@
+use crate::ast::Object;
+use crate::interpreter::RuntimeError;
+use crate::token::Token;
+use std::collections::HashMap;
+
+/// Stores the values bound to variable names.
+#[derive(Debug, Default)]
+pub struct Environment {
+    values: HashMap<String, Object>,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn define(&mut self, name: &str, value: Object) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    pub fn get(&self, name: &Token) -> Result<Object, RuntimeError> {
+        self.values.get(&name.lexeme).cloned().ok_or_else(|| {
+            RuntimeError::new(name, &format!("Undefined variable '{}'.", name.lexeme))
+        })
+    }
+
+    /// Returns the bound variables, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Object)> {
+        self.values.iter()
+    }
+
+    /// Looks up a variable by name, without needing a token from the source.
+    pub fn value(&self, name: &str) -> Option<Object> {
+        self.values.get(name).cloned()
+    }
+
+    pub fn assign(&mut self, name: &Token, value: Object) -> Result<(), RuntimeError> {
+        match self.values.get_mut(&name.lexeme) {
+            Some(slot) => {
+                *slot = value;
+                Ok(())
+            }
+            None => Err(RuntimeError::new(
+                name,
+                &format!("Undefined variable '{}'.", name.lexeme),
+            )),
+        }
+    }
+}
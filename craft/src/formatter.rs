@@ -0,0 +1,249 @@
+use crate::token::Token;
+use crate::token_type::TokenType;
+use std::fmt::Display;
+use std::path::PathBuf;
+
+const INDENT: &str = "    ";
+
+/// Lays out tokens scanned with [`crate::scanner::Scanner::with_trivia`] as canonically formatted
+/// source code. Comments are kept, and a run of blank lines between statements becomes one.
+struct Formatter<'a> {
+    output: String,
+    /// The previous token written, other than a comment.
+    previous: Option<&'a Token>,
+    /// The source line of the previous token or comment written.
+    last_line: usize,
+    /// Whether the previous token was a prefix operator.
+    after_unary: bool,
+    /// Whether a line break is due before the next token.
+    pending_break: bool,
+    /// Whether the previous token was a `/* ... */` comment, which code can follow on its line.
+    after_block_comment: bool,
+    at_line_start: bool,
+    braces: usize,
+    parens: usize,
+}
+
+/// Returns formatted source code made from the given tokens.
+pub fn format_tokens(tokens: &[Token]) -> String {
+    let mut formatter = Formatter {
+        output: String::new(),
+        previous: None,
+        last_line: 0,
+        after_unary: false,
+        pending_break: false,
+        after_block_comment: false,
+        at_line_start: true,
+        braces: 0,
+        parens: 0,
+    };
+
+    let mut index = 0;
+    while let Some(token) = tokens.get(index) {
+        // A `;` after comments is written before them, so that it stays on the line of the code
+        // it ends rather than on a line of its own.
+        let comments = tokens[index..]
+            .iter()
+            .take_while(|token| token.variant == TokenType::COMMENT)
+            .count();
+        if let Some(semicolon) = tokens
+            .get(index + comments)
+            .filter(|next| comments > 0 && next.variant == TokenType::SEMICOLON)
+            .filter(|_| formatter.previous.is_some())
+        {
+            // The comments are still laid out relative to the code before the `;`.
+            let line = formatter.last_line;
+            formatter.write(semicolon);
+            formatter.last_line = line;
+            for comment in &tokens[index..index + comments] {
+                formatter.write(comment);
+            }
+            formatter.last_line = formatter.last_line.max(semicolon.line);
+
+            index += comments + 1;
+            continue;
+        }
+
+        if token.variant != TokenType::EOF {
+            formatter.write(token);
+        }
+        index += 1;
+    }
+
+    let mut retval = formatter.output.trim_end().to_string();
+    if !retval.is_empty() {
+        retval.push('\n');
+    }
+    retval
+}
+
+impl<'a> Formatter<'a> {
+    fn write(&mut self, token: &'a Token) {
+        let is_comment = token.variant == TokenType::COMMENT;
+        let line_comment = is_comment && token.lexeme.starts_with("//");
+        let trailing_comment = is_comment && self.last_line == first_line(token);
+
+        if trailing_comment {
+            self.output.push(' ');
+            self.output.push_str(token.lexeme.trim_end());
+            self.last_line = token.line;
+            self.after_block_comment = !line_comment;
+            if line_comment {
+                self.newline();
+            }
+            return;
+        }
+
+        let after_line_end = self.after_block_comment && first_line(token) > self.last_line;
+        if self.pending_break || after_line_end || (is_comment && !self.at_line_start) {
+            self.newline();
+        }
+
+        if token.variant == TokenType::RIGHT_BRACE {
+            self.braces = self.braces.saturating_sub(1);
+        }
+
+        if self.at_line_start {
+            self.start_line(token);
+        } else if self.space_before(token) {
+            self.output.push(' ');
+        }
+
+        match token.variant {
+            TokenType::COMMENT => self.output.push_str(token.lexeme.trim_end()),
+            _ => self.output.push_str(&token.lexeme),
+        }
+        self.at_line_start = false;
+
+        match token.variant {
+            TokenType::LEFT_PAREN => self.parens += 1,
+            TokenType::RIGHT_PAREN => self.parens = self.parens.saturating_sub(1),
+            TokenType::LEFT_BRACE => self.braces += 1,
+            _ => (),
+        }
+
+        self.pending_break = match token.variant {
+            TokenType::SEMICOLON => self.parens == 0,
+            TokenType::LEFT_BRACE | TokenType::RIGHT_BRACE => true,
+            TokenType::COMMENT => line_comment,
+            _ => false,
+        };
+        self.after_block_comment = is_comment && !line_comment;
+        if !is_comment {
+            self.after_unary = matches!(
+                token.variant,
+                TokenType::BANG | TokenType::MINUS | TokenType::TILDE
+            ) && !self.previous.is_some_and(ends_operand);
+            self.previous = Some(token);
+        }
+        self.last_line = token.line;
+    }
+
+    fn newline(&mut self) {
+        if !self.at_line_start {
+            self.output.push('\n');
+        }
+        self.at_line_start = true;
+        self.pending_break = false;
+    }
+
+    /// Indents a new line for the token, after a blank line if there was one in the source.
+    fn start_line(&mut self, token: &Token) {
+        if let Some(previous) = self.previous {
+            let blank_line = first_line(token) > self.last_line + 1;
+            let inside_braces = previous.variant == TokenType::LEFT_BRACE
+                || token.variant == TokenType::RIGHT_BRACE;
+
+            if blank_line && !inside_braces {
+                self.output.push('\n');
+            }
+        }
+
+        // Lines that continue a statement, after a comment in the middle of it, are indented
+        // once more.
+        let continuation = self.previous.is_some_and(|previous| {
+            !matches!(
+                previous.variant,
+                TokenType::SEMICOLON | TokenType::LEFT_BRACE | TokenType::RIGHT_BRACE
+            ) && token.variant != TokenType::RIGHT_BRACE
+        });
+
+        let depth = self.braces + usize::from(continuation);
+        self.output.push_str(&INDENT.repeat(depth));
+    }
+
+    fn space_before(&self, token: &Token) -> bool {
+        let Some(previous) = self.previous else {
+            return false;
+        };
+
+        // Code after a comment on the same line is kept apart from it.
+        if self.after_block_comment {
+            return true;
+        }
+
+        if self.after_unary {
+            return false;
+        }
+
+        match (&previous.variant, &token.variant) {
+            (TokenType::LEFT_PAREN | TokenType::DOT | TokenType::INTERPOLATION(_), _) => false,
+            // The rest of an interpolated string, after an interpolated expression.
+            (_, TokenType::STRING(_) | TokenType::INTERPOLATION(_))
+                if token.lexeme.starts_with('}') =>
+            {
+                false
+            }
+            (
+                _,
+                TokenType::RIGHT_PAREN | TokenType::COMMA | TokenType::SEMICOLON | TokenType::DOT,
+            ) => false,
+            // A call.
+            (_, TokenType::LEFT_PAREN) => !ends_operand(previous),
+            _ => true,
+        }
+    }
+}
+
+/// Returns whether the token can end an operand, so that a `-` after it is a binary operator and
+/// a `(` after it starts a call.
+fn ends_operand(token: &Token) -> bool {
+    match token.variant {
+        #[cfg(feature = "bigint")]
+        TokenType::BIG_INTEGER(_) => true,
+        _ => matches!(
+            token.variant,
+            TokenType::IDENTIFIER(_)
+                | TokenType::STRING(_)
+                | TokenType::NUMBER(_)
+                | TokenType::INTEGER(_)
+                | TokenType::TRUE
+                | TokenType::FALSE
+                | TokenType::NIL
+                | TokenType::THIS
+                | TokenType::SUPER
+                | TokenType::RIGHT_PAREN
+        ),
+    }
+}
+
+/// Returns the line the token starts on. Tokens record the line they end on, which differs for
+/// strings that span several lines.
+fn first_line(token: &Token) -> usize {
+    token.line - token.lexeme.matches('\n').count()
+}
+
+/// Files that `craft fmt --check` found not to be formatted.
+#[derive(Debug)]
+pub struct Unformatted(pub Vec<PathBuf>);
+
+impl Display for Unformatted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0.len() {
+            1 => write!(f, "1 file needs formatting"),
+            count => write!(f, "{} files need formatting", count),
+        }
+    }
+}
+
+impl std::error::Error for Unformatted {}
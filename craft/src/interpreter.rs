@@ -1,26 +1,172 @@
+use std::cmp::Ordering;
+use std::fmt::Display;
+use std::io::{self, Write};
+
+#[cfg(feature = "bigint")]
+use crate::bigint::{self, BigInt};
+use crate::environment::Environment;
+use crate::native::{self, NativeFunction};
+use crate::token::Token;
 use crate::{ast::*, token::TokenType};
+#[cfg(feature = "bigint")]
+use num_traits::Zero;
+use std::rc::Rc;
+
+/// An error raised while executing a program.
+#[derive(Debug, Clone)]
+pub struct RuntimeError {
+    /// The token where the error occurred, if known. Native functions don't have access to the
+    /// call site, so the interpreter fills it in for them.
+    pub token: Option<Token>,
+    pub message: String,
+}
+
+impl RuntimeError {
+    pub fn new(token: &Token, message: &str) -> Self {
+        Self {
+            token: Some(token.clone()),
+            message: message.to_string(),
+        }
+    }
+
+    /// Creates an error that isn't tied to a location in the source yet.
+    pub fn message(message: &str) -> Self {
+        Self {
+            token: None,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.token {
+            Some(token) => write!(f, "{}\n[line {}]", self.message, token.line),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
 
-pub struct Interpreter;
+impl std::error::Error for RuntimeError {}
 
-impl Visitor<Object> for Interpreter {
-    fn visit_expr(&mut self, expr: &Expr) -> Object {
+pub(crate) type RuntimeResult<T> = Result<T, RuntimeError>;
+
+pub struct Interpreter {
+    globals: Environment,
+    stdout: Box<dyn Write>,
+    stderr: Box<dyn Write>,
+    /// The error from the last failed write to `stdout`, which ends the program.
+    output_error: Option<io::Error>,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Visitor<RuntimeResult<Object>> for Interpreter {
+    fn visit_expr(&mut self, expr: &Expr) -> RuntimeResult<Object> {
         match expr {
+            Expr::Assign(expr) => self.visit_assign_expr(expr),
             Expr::Binary(expr) => self.visit_binary_expr(expr),
+            Expr::Call(expr) => self.visit_call_expr(expr),
+            Expr::Comma(expr) => self.visit_comma_expr(expr),
+            Expr::Conditional(expr) => self.visit_conditional_expr(expr),
             Expr::Grouping(expr) => self.visit_grouping_expr(expr),
+            Expr::Interpolation(expr) => self.visit_interpolation_expr(expr),
             Expr::Literal(expr) => self.visit_literal_expr(expr),
             Expr::Unary(expr) => self.visit_unary_expr(expr),
+            Expr::Variable(expr) => self.visit_variable_expr(expr),
         }
     }
 
-    fn visit_binary_expr(&mut self, expr: &Binary) -> Object {
-        let left = Interpreter::evaluate(expr.left.clone());
-        let right = Interpreter::evaluate(expr.right.clone());
+    fn visit_assign_expr(&mut self, expr: &Assign) -> RuntimeResult<Object> {
+        let value = self.evaluate(&expr.value)?;
+        self.globals.assign(&expr.name, value.clone())?;
+        Ok(value)
+    }
 
-        match (left, right) {
+    fn visit_binary_expr(&mut self, expr: &Binary) -> RuntimeResult<Object> {
+        let left = self.evaluate(&expr.left)?;
+        let right = self.evaluate(&expr.right)?;
+
+        if is_bitwise(&expr.operator.variant) {
+            return bitwise(&expr.operator, &left, &right);
+        }
+
+        // Comparing an integer with a float is exact, since converting the integer to a float
+        // could round it.
+        if is_comparison(&expr.operator.variant) {
+            let ordering = match (&left, &right) {
+                (Object::Int(left), Object::Number(right)) => {
+                    Some(compare_int_float(*left, *right))
+                }
+                (Object::Number(left), Object::Int(right)) => {
+                    Some(compare_int_float(*right, *left).map(Ordering::reverse))
+                }
+                _ => None,
+            };
+            if let Some(ordering) = ordering {
+                return Ok(Object::Boolean(comparison(
+                    &expr.operator.variant,
+                    ordering,
+                )));
+            }
+        }
+
+        // An integer mixed with a float is promoted to a float.
+        let (left, right) = match (left, right) {
+            (Object::Int(left), Object::Number(right)) => {
+                (Object::Number(left as f64), Object::Number(right))
+            }
+            (Object::Number(left), Object::Int(right)) => {
+                (Object::Number(left), Object::Number(right as f64))
+            }
+            #[cfg(feature = "bigint")]
+            (Object::BigInt(left), Object::Number(right)) => {
+                (Object::Number(bigint::to_f64(&left)), Object::Number(right))
+            }
+            #[cfg(feature = "bigint")]
+            (Object::Number(left), Object::BigInt(right)) => {
+                (Object::Number(left), Object::Number(bigint::to_f64(&right)))
+            }
+            operands => operands,
+        };
+
+        let value = match (left, right) {
+            (Object::Int(left), Object::Int(right)) => {
+                return integer_arithmetic(&expr.operator, left, right);
+            }
+            #[cfg(feature = "bigint")]
+            (Object::BigInt(left), Object::BigInt(right)) => {
+                return bigint::arithmetic(&expr.operator, &left, &right);
+            }
+            #[cfg(feature = "bigint")]
+            (Object::BigInt(left), Object::Int(right)) => {
+                return bigint::arithmetic(&expr.operator, &left, &BigInt::from(right));
+            }
+            #[cfg(feature = "bigint")]
+            (Object::Int(left), Object::BigInt(right)) => {
+                return bigint::arithmetic(&expr.operator, &BigInt::from(left), &right);
+            }
             (Object::Number(left), Object::Number(right)) => match expr.operator.variant {
                 TokenType::MINUS => Object::Number(left - right),
                 TokenType::SLASH => Object::Number(left / right),
                 TokenType::STAR => Object::Number(left * right),
+                TokenType::PERCENT if right == 0.0 => {
+                    return Err(RuntimeError::new(&expr.operator, "Modulo by zero."));
+                }
+                TokenType::PERCENT => Object::Number(left % right),
+                TokenType::TILDE_SLASH if right == 0.0 => {
+                    return Err(RuntimeError::new(
+                        &expr.operator,
+                        "Integer division by zero.",
+                    ));
+                }
+                // Rounds towards zero, so that `a == b * (a ~/ b) + a % b`.
+                TokenType::TILDE_SLASH => Object::Number((left / right).trunc()),
+                TokenType::STAR_STAR => Object::Number(left.powf(right)),
                 TokenType::PLUS => Object::Number(left + right),
                 TokenType::GREATER => Object::Boolean(left > right),
                 TokenType::GREATER_EQUAL => Object::Boolean(left >= right),
@@ -47,77 +193,442 @@ impl Visitor<Object> for Interpreter {
                 _ => Object::Nil,
             },
             _ => Object::Nil,
+        };
+
+        Ok(value)
+    }
+
+    fn visit_call_expr(&mut self, expr: &Call) -> RuntimeResult<Object> {
+        let callee = self.evaluate(&expr.callee)?;
+
+        let mut arguments = Vec::new();
+        for argument in &expr.arguments {
+            arguments.push(self.evaluate(argument)?);
         }
+
+        self.call(&callee, &arguments).map_err(|mut err| {
+            err.token.get_or_insert_with(|| expr.paren.clone());
+            err
+        })
     }
 
-    fn visit_grouping_expr(&mut self, expr: &Grouping) -> Object {
-        Interpreter::evaluate(expr.expression.clone())
+    fn visit_comma_expr(&mut self, expr: &Comma) -> RuntimeResult<Object> {
+        self.evaluate(&expr.left)?;
+        self.evaluate(&expr.right)
     }
 
-    fn visit_literal_expr(&mut self, expr: &Literal) -> Object {
-        expr.value.clone()
+    fn visit_conditional_expr(&mut self, expr: &Conditional) -> RuntimeResult<Object> {
+        if Interpreter::is_truthy(self.evaluate(&expr.condition)?) {
+            self.evaluate(&expr.then_branch)
+        } else {
+            self.evaluate(&expr.else_branch)
+        }
     }
 
-    fn visit_unary_expr(&mut self, expr: &Unary) -> Object {
-        let right = Interpreter::evaluate(expr.right.clone());
+    fn visit_grouping_expr(&mut self, expr: &Grouping) -> RuntimeResult<Object> {
+        self.evaluate(&expr.expression)
+    }
 
-        match expr.operator.variant {
-            TokenType::MINUS => {
-                if let Object::Number(right) = right {
-                    Object::Number(-right)
-                } else {
-                    Object::Nil
-                }
-            }
+    fn visit_interpolation_expr(&mut self, expr: &Interpolation) -> RuntimeResult<Object> {
+        let mut value = String::new();
+        for part in &expr.parts {
+            value.push_str(&self.evaluate(part)?.to_string());
+        }
+
+        Ok(Object::String(value))
+    }
+
+    fn visit_literal_expr(&mut self, expr: &Literal) -> RuntimeResult<Object> {
+        Ok(expr.value.clone())
+    }
+
+    fn visit_unary_expr(&mut self, expr: &Unary) -> RuntimeResult<Object> {
+        let right = self.evaluate(&expr.right)?;
+
+        let value = match expr.operator.variant {
+            TokenType::MINUS => match right {
+                Object::Number(right) => Object::Number(-right),
+                Object::Int(right) => match right.checked_neg() {
+                    Some(value) => Object::Int(value),
+                    // Only the smallest integer overflows, as `0 - right` does.
+                    None => return overflow(&expr.operator, 0, right),
+                },
+                #[cfg(feature = "bigint")]
+                Object::BigInt(right) => bigint::normalize(-right),
+                _ => Object::Nil,
+            },
             TokenType::BANG => Object::Boolean(!Interpreter::is_truthy(right)),
+            TokenType::TILDE => match right {
+                #[cfg(feature = "bigint")]
+                Object::BigInt(right) => bigint::normalize(!right),
+                right => Object::Int(!integer(
+                    &expr.operator,
+                    &right,
+                    "Operand must be an integer.",
+                )?),
+            },
             _ => Object::Nil,
+        };
+
+        Ok(value)
+    }
+
+    fn visit_variable_expr(&mut self, expr: &Variable) -> RuntimeResult<Object> {
+        self.globals.get(&expr.name)
+    }
+}
+
+/// Evaluates an arithmetic or comparison operator on two integers. `/` and negative powers give
+/// floats.
+fn integer_arithmetic(operator: &Token, left: i64, right: i64) -> RuntimeResult<Object> {
+    let value = match operator.variant {
+        TokenType::PLUS => left.checked_add(right),
+        TokenType::MINUS => left.checked_sub(right),
+        TokenType::STAR => left.checked_mul(right),
+        TokenType::SLASH => return Ok(Object::Number(left as f64 / right as f64)),
+        TokenType::PERCENT if right == 0 => {
+            return Err(RuntimeError::new(operator, "Modulo by zero."));
+        }
+        TokenType::PERCENT => left.checked_rem(right),
+        TokenType::TILDE_SLASH if right == 0 => {
+            return Err(RuntimeError::new(operator, "Integer division by zero."));
+        }
+        TokenType::TILDE_SLASH => left.checked_div(right),
+        TokenType::STAR_STAR if right < 0 => {
+            return Ok(Object::Number((left as f64).powf(right as f64)));
+        }
+        TokenType::STAR_STAR => match u32::try_from(right) {
+            Ok(exponent) => left.checked_pow(exponent),
+            // Only the powers of 0, 1 and -1 stay small for exponents this large.
+            Err(_) if left == 0 || left == 1 => Some(left),
+            Err(_) if left == -1 => Some(if right % 2 == 0 { 1 } else { -1 }),
+            Err(_) => None,
+        },
+        TokenType::GREATER => return Ok(Object::Boolean(left > right)),
+        TokenType::GREATER_EQUAL => return Ok(Object::Boolean(left >= right)),
+        TokenType::LESS => return Ok(Object::Boolean(left < right)),
+        TokenType::LESS_EQUAL => return Ok(Object::Boolean(left <= right)),
+        TokenType::BANG_EQUAL => return Ok(Object::Boolean(left != right)),
+        TokenType::EQUAL_EQUAL => return Ok(Object::Boolean(left == right)),
+        // As for big integers, only a syntax tree loaded from JSON can have another operator.
+        _ => {
+            return Err(RuntimeError::new(
+                operator,
+                "Invalid operator for integers.",
+            ))
+        }
+    };
+
+    match value {
+        Some(value) => Ok(Object::Int(value)),
+        None => overflow(operator, left, right),
+    }
+}
+
+/// Evaluates an integer operation that overflowed 64 bits again with big integers.
+#[cfg(feature = "bigint")]
+fn overflow(operator: &Token, left: i64, right: i64) -> RuntimeResult<Object> {
+    let (left, right) = (BigInt::from(left), BigInt::from(right));
+    if is_bitwise(&operator.variant) {
+        bigint::bitwise(operator, &left, &right)
+    } else {
+        bigint::arithmetic(operator, &left, &right)
+    }
+}
+
+/// Reports an integer operation that overflowed 64 bits.
+#[cfg(not(feature = "bigint"))]
+fn overflow(operator: &Token, _left: i64, _right: i64) -> RuntimeResult<Object> {
+    Err(RuntimeError::new(operator, "Integer overflow."))
+}
+
+fn is_comparison(operator: &TokenType) -> bool {
+    matches!(
+        operator,
+        TokenType::GREATER
+            | TokenType::GREATER_EQUAL
+            | TokenType::LESS
+            | TokenType::LESS_EQUAL
+            | TokenType::BANG_EQUAL
+            | TokenType::EQUAL_EQUAL
+    )
+}
+
+/// Returns how an integer orders against a float, without rounding either, or `None` if the float
+/// is NaN.
+fn compare_int_float(int: i64, float: f64) -> Option<Ordering> {
+    // -2^63 and 2^63 are the bounds of `i64`, and are exact as floats.
+    const MIN: f64 = i64::MIN as f64;
+    if float.is_nan() {
+        return None;
+    } else if float >= -MIN {
+        return Some(Ordering::Less);
+    } else if float < MIN {
+        return Some(Ordering::Greater);
+    }
+
+    // The float is within the range of `i64` here, so its whole part converts exactly.
+    let whole = float.floor();
+    match int.cmp(&(whole as i64)) {
+        Ordering::Equal if float != whole => Some(Ordering::Less),
+        ordering => Some(ordering),
+    }
+}
+
+/// Returns the result of a comparison operator for the operands' ordering, where `None` means
+/// they're unordered.
+fn comparison(operator: &TokenType, ordering: Option<Ordering>) -> bool {
+    match operator {
+        TokenType::GREATER => ordering == Some(Ordering::Greater),
+        TokenType::GREATER_EQUAL => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+        TokenType::LESS => ordering == Some(Ordering::Less),
+        TokenType::LESS_EQUAL => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+        TokenType::EQUAL_EQUAL => ordering == Some(Ordering::Equal),
+        TokenType::BANG_EQUAL => ordering != Some(Ordering::Equal),
+        _ => unreachable!("Not a comparison operator: {:?}", operator),
+    }
+}
+
+fn is_bitwise(operator: &TokenType) -> bool {
+    matches!(
+        operator,
+        TokenType::AMPERSAND
+            | TokenType::PIPE
+            | TokenType::CARET
+            | TokenType::LESS_LESS
+            | TokenType::GREATER_GREATER
+    )
+}
+
+/// Evaluates a bitwise or shift operator, whose operands must be integers.
+fn bitwise(operator: &Token, left: &Object, right: &Object) -> RuntimeResult<Object> {
+    let message = "Operands must be integers.";
+
+    #[cfg(feature = "bigint")]
+    if matches!(left, Object::BigInt(_)) || matches!(right, Object::BigInt(_)) {
+        let left = bigint::from_object(left).ok_or_else(|| RuntimeError::new(operator, message))?;
+        let right =
+            bigint::from_object(right).ok_or_else(|| RuntimeError::new(operator, message))?;
+        return bigint::bitwise(operator, &left, &right);
+    }
+
+    let left = integer(operator, left, message)?;
+    let right = integer(operator, right, message)?;
+
+    // Big integers can be shifted by any number of bits.
+    #[cfg(feature = "bigint")]
+    if matches!(
+        operator.variant,
+        TokenType::LESS_LESS | TokenType::GREATER_GREATER
+    ) && !(0..i64::from(i64::BITS)).contains(&right)
+    {
+        return bigint::bitwise(operator, &BigInt::from(left), &BigInt::from(right));
+    }
+
+    let value = match operator.variant {
+        TokenType::AMPERSAND => left & right,
+        TokenType::PIPE => left | right,
+        TokenType::CARET => left ^ right,
+        TokenType::LESS_LESS => {
+            let shift = shift_amount(operator, right)?;
+            let value = left << shift;
+
+            // Shifting out any bits but copies of the sign bit overflows.
+            if value >> shift != left {
+                return overflow(operator, left, right);
+            }
+            value
         }
+        TokenType::GREATER_GREATER => left >> shift_amount(operator, right)?,
+        _ => unreachable!("Not a bitwise operator: {:?}", operator),
+    };
+
+    Ok(Object::Int(value))
+}
+
+/// Returns the amount to shift an integer by, which must be less than its 64 bits.
+fn shift_amount(operator: &Token, amount: i64) -> RuntimeResult<u32> {
+    if amount < 0 {
+        return Err(RuntimeError::new(
+            operator,
+            "Shift amount must not be negative.",
+        ));
+    }
+
+    u32::try_from(amount)
+        .ok()
+        .filter(|amount| *amount < i64::BITS)
+        .ok_or_else(|| RuntimeError::new(operator, "Shift amount must be less than 64."))
+}
+
+/// Returns the value as an integer, or reports the message if it isn't a whole number. Floats
+/// with whole values are accepted too.
+fn integer(operator: &Token, value: &Object, message: &str) -> RuntimeResult<i64> {
+    match value {
+        Object::Int(n) => Some(*n),
+        Object::Number(n) if n.fract() == 0.0 => native::truncate(*n),
+        _ => None,
     }
+    .ok_or_else(|| RuntimeError::new(operator, message))
 }
 
-impl StmtVisitor<()> for Interpreter {
-    fn visit_stmt(&mut self, stmt: &Stmt) -> () {
+impl StmtVisitor<RuntimeResult<()>> for Interpreter {
+    fn visit_stmt(&mut self, stmt: &Stmt) -> RuntimeResult<()> {
         match stmt {
             Stmt::Expression(stmt) => self.visit_expression_stmt(stmt),
             Stmt::Print(stmt) => self.visit_print_stmt(stmt),
-            Stmt::Empty => (),
+            Stmt::Var(stmt) => self.visit_var_stmt(stmt),
+            Stmt::Empty => Ok(()),
         }
     }
 
-    fn visit_expression_stmt(&mut self, stmt: &Expression) -> () {
-        Interpreter::evaluate(stmt.expr.clone());
+    fn visit_expression_stmt(&mut self, stmt: &Expression) -> RuntimeResult<()> {
+        self.evaluate(&stmt.expr)?;
+        Ok(())
     }
 
-    fn visit_print_stmt(&mut self, stmt: &Print) -> () {
-        let value = Interpreter::evaluate(stmt.expr.clone());
-        match value {
-            Object::Nil => println!("nil"),
-            Object::Boolean(value) => println!("{}", value),
-            Object::Number(value) => println!("{}", value),
-            Object::String(value) => println!("{}", value),
-            Object::Identifier(value) => println!("Ientifier '{}'", value),
-        }
+    fn visit_print_stmt(&mut self, stmt: &Print) -> RuntimeResult<()> {
+        let value = self.evaluate(&stmt.expr)?;
+        writeln!(self.stdout, "{}", value).map_err(|err| {
+            let error = RuntimeError::message(&err.to_string());
+            self.output_error = Some(err);
+            error
+        })
+    }
+
+    fn visit_var_stmt(&mut self, stmt: &Var) -> RuntimeResult<()> {
+        let value = match &stmt.initializer {
+            Some(initializer) => self.evaluate(initializer)?,
+            None => Object::Nil,
+        };
+
+        self.globals.define(&stmt.name.lexeme, value);
+        Ok(())
     }
 }
 
 impl Interpreter {
+    /// Creates an interpreter whose globals are seeded with the standard native functions.
+    pub fn new() -> Self {
+        let mut interpreter = Self {
+            globals: Environment::new(),
+            stdout: Box::new(io::stdout()),
+            stderr: Box::new(io::stderr()),
+            output_error: None,
+        };
+
+        interpreter.define_native("clock", 0, native::clock);
+        interpreter.define_native("int", 1, native::int);
+        interpreter.define_native("float", 1, native::float);
+
+        interpreter
+    }
+
+    /// Registers a Rust closure as a global function callable from Lox code.
+    pub fn define_native(
+        &mut self,
+        name: &str,
+        arity: usize,
+        function: impl Fn(&[Object]) -> Result<Object, RuntimeError> + 'static,
+    ) {
+        let native = NativeFunction::new(name, arity, function);
+        self.globals
+            .define(name, Object::NativeFunction(Rc::new(native)));
+    }
+
+    /// Creates an interpreter that writes program output and error messages to the given sinks.
+    pub fn with_output(stdout: Box<dyn Write>, stderr: Box<dyn Write>) -> Self {
+        let mut interpreter = Self::new();
+        interpreter.set_stdout(stdout);
+        interpreter.set_stderr(stderr);
+        interpreter
+    }
+
+    /// Redirects the output of `print` statements.
+    pub fn set_stdout(&mut self, stdout: Box<dyn Write>) {
+        self.stdout = stdout;
+    }
+
+    /// Redirects error messages.
+    pub fn set_stderr(&mut self, stderr: Box<dyn Write>) {
+        self.stderr = stderr;
+    }
+
+    /// Returns the error that stopped the program from writing its output, if any, and clears it.
+    pub fn take_output_error(&mut self) -> Option<io::Error> {
+        self.output_error.take()
+    }
+
+    /// Writes a message to the error output.
+    pub fn report(&mut self, message: &dyn Display) {
+        // There is nowhere left to report a failure to write an error message.
+        let _ = writeln!(self.stderr, "{}", message);
+    }
+
+    pub fn runtime_error(&mut self, error: &RuntimeError) {
+        crate::runtime_error(error);
+        self.report(error);
+    }
+
+    pub fn globals(&self) -> &Environment {
+        &self.globals
+    }
+
+    pub fn globals_mut(&mut self) -> &mut Environment {
+        &mut self.globals
+    }
+
+    /// Calls a callable value with already evaluated arguments.
+    pub fn call(&mut self, callee: &Object, arguments: &[Object]) -> RuntimeResult<Object> {
+        let Object::NativeFunction(function) = callee else {
+            return Err(RuntimeError::message(
+                "Can only call functions and classes.",
+            ));
+        };
+
+        if arguments.len() != function.arity {
+            return Err(RuntimeError::message(&format!(
+                "Expected {} arguments but got {}.",
+                function.arity,
+                arguments.len()
+            )));
+        }
+
+        function.call(arguments)
+    }
+
     fn is_truthy(obj: Object) -> bool {
         match obj {
             Object::Nil => false,
             Object::Boolean(value) => value,
             Object::String(value) => !value.is_empty(),
             Object::Number(value) => value != 0.0,
+            Object::Int(value) => value != 0,
+            // Zero is normally an `Int`, but a syntax tree loaded from JSON can hold any value.
+            #[cfg(feature = "bigint")]
+            Object::BigInt(value) => !value.is_zero(),
             Object::Identifier(_) => true,
+            Object::NativeFunction(_) => true,
         }
     }
 
-    pub fn evaluate(expression: Expr) -> Object {
-        Interpreter {}.visit_expr(&expression)
+    pub fn evaluate(&mut self, expression: &Expr) -> RuntimeResult<Object> {
+        self.visit_expr(expression)
     }
 
-    pub fn interpret(statements: Vec<Stmt>) {
+    pub fn execute(&mut self, statement: &Stmt) -> RuntimeResult<()> {
+        self.visit_stmt(statement)
+    }
+
+    /// Executes the given statements, reporting the first runtime error to the error output.
+    pub fn interpret(&mut self, statements: Vec<Stmt>) -> RuntimeResult<()> {
         for statement in statements {
-            Interpreter {}.visit_stmt(&statement);
+            if let Err(err) = self.execute(&statement) {
+                self.runtime_error(&err);
+                return Err(err);
+            }
         }
+
+        Ok(())
     }
 }
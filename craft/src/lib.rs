@@ -3,77 +3,273 @@ extern crate lazy_static;
 
 pub mod ast;
 pub mod ast_printer;
+#[cfg(feature = "bigint")]
+pub mod bigint;
+pub mod engine;
+pub mod environment;
+pub mod formatter;
 pub mod interpreter;
+pub mod native;
+pub mod output;
 pub mod parser;
+pub mod repl;
 pub mod scanner;
+pub mod source_printer;
 pub mod token;
 pub mod token_type;
 
-use interpreter::Interpreter;
+pub use engine::{Engine, Error};
+pub use repl::run_prompt;
+
+use ast::Stmt;
+use interpreter::RuntimeError;
 use log::*;
-use parser::Parser;
-use scanner::Scanner;
-use std::io::{stdout, Write};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::cell::RefCell;
+use std::io::{self, Write};
 use std::{
     fs::{self},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
+use token::{Token, TokenType};
 
 pub type EmpResult = Result<(), Box<dyn std::error::Error>>;
 pub type Eer = Result<(), ()>;
-pub static HAD_ERROR: AtomicBool = AtomicBool::new(false);
+
+// Exit codes, following the conventions of BSD's `sysexits.h`.
+/// The command was used incorrectly.
+pub const EX_USAGE: u8 = 64;
+/// The input script has scan or parse errors.
+pub const EX_DATAERR: u8 = 65;
+/// The input file could not be read.
+pub const EX_NOINPUT: u8 = 66;
+/// The script failed at runtime.
+pub const EX_SOFTWARE: u8 = 70;
+/// Output could not be written.
+pub const EX_IOERR: u8 = 74;
+
+/// The exit status of `craft fmt --check` when it finds files that aren't formatted. It isn't a
+/// sysexits code, because unformatted files are a result of the check rather than a failure to run
+/// it, like `rustfmt --check` and `diff`.
+pub const EX_UNFORMATTED: u8 = 1;
+
+// Error state is tracked per thread, so that independent engines on different threads don't
+// observe each other's errors.
+thread_local! {
+    static DIAGNOSTICS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
 
 pub fn error(line: usize, message: &str) {
-    HAD_ERROR.store(true, Ordering::SeqCst);
     report(line, "", message);
 }
 
+/// Reports a compile error at the given token.
+pub fn token_error(token: &Token, message: &str) {
+    if token.variant == TokenType::EOF {
+        report(token.line, " at end", message);
+    } else {
+        report(token.line, &format!(" at '{}'", token.lexeme), message);
+    }
+}
+
+pub fn runtime_error(error: &RuntimeError) {
+    debug!("{}", error);
+}
+
 fn report(line: usize, loc: &str, message: &str) {
-    info!("[line {}] Error {} : {}", line, loc, message);
+    let diagnostic = format!("[line {}] Error{}: {}", line, loc, message);
+    debug!("{}", diagnostic);
+    DIAGNOSTICS.with_borrow_mut(|diagnostics| diagnostics.push(diagnostic));
+}
+
+/// Returns the compile errors reported on this thread since the last call, and clears them.
+pub fn take_diagnostics() -> Vec<String> {
+    DIAGNOSTICS.take()
+}
+
+/// Returns the process exit code that corresponds to the given error.
+pub fn exit_code(err: &(dyn std::error::Error + 'static)) -> u8 {
+    if let Some(err) = err.downcast_ref::<Error>() {
+        return match err {
+            Error::Compile(_) => EX_DATAERR,
+            Error::Runtime(_) | Error::UndefinedGlobal(_) => EX_SOFTWARE,
+            Error::Io(_) => EX_IOERR,
+        };
+    }
+
+    if err.is::<formatter::Unformatted>() {
+        return EX_UNFORMATTED;
+    }
+
+    if err.is::<NoInput>() {
+        return EX_NOINPUT;
+    }
+
+    if err.is::<io::Error>() {
+        return EX_IOERR;
+    }
+
+    #[cfg(feature = "serde")]
+    if err.is::<serde_json::Error>() {
+        return EX_DATAERR;
+    }
+
+    EX_SOFTWARE
+}
+
+/// Returns the directory where logs and REPL history are kept.
+pub fn state_dir() -> Option<PathBuf> {
+    home::home_dir().map(|home| home.join(".local").join("state"))
+}
+
+/// An input file that could not be read, as opposed to an I/O error while writing output.
+#[derive(Debug)]
+pub struct NoInput {
+    pub path: PathBuf,
+    pub source: io::Error,
+}
+
+impl std::fmt::Display for NoInput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Can't read {}: {}", self.path.display(), self.source)
+    }
+}
+
+impl std::error::Error for NoInput {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+fn read_source(path: &Path) -> Result<String, NoInput> {
+    fs::read_to_string(path).map_err(|source| NoInput {
+        path: path.to_path_buf(),
+        source,
+    })
 }
 
 pub fn run_file(path: PathBuf) -> EmpResult {
-    println!("Running {:?}", path);
-    let contents = fs::read_to_string(path)?;
+    info!("Running {:?}", path);
+    let contents = read_source(&path)?;
     run(&contents)
 }
 
-pub fn run_prompt() -> EmpResult {
-    println!("REPL mode: Type code to run");
-    loop {
-        print!("> ");
-        stdout().flush().unwrap();
-        let mut input = String::new();
-        std::io::stdin().read_line(&mut input)?;
-        let input = input.trim();
+/// Runs a syntax tree saved as JSON, as printed by `dump_file` with `Dump::Ast`.
+pub fn run_ast_file(path: PathBuf) -> EmpResult {
+    info!("Running syntax tree {:?}", path);
+    let contents = read_source(&path)?;
+    let stmts = stmts_from_json(&contents)?;
+    Engine::new().execute(&stmts)?;
+    Ok(())
+}
+
+/// What to print instead of running a script.
+#[derive(Clone, Copy, Debug)]
+pub enum Dump {
+    Tokens,
+    Ast,
+}
+
+/// Prints the tokens or the syntax tree of a file, as text or as JSON, without running it.
+pub fn dump_file(path: PathBuf, dump: Dump, json: bool) -> EmpResult {
+    let contents = read_source(&path)?;
+    let mut engine = Engine::new();
+
+    match dump {
+        Dump::Tokens => {
+            let tokens = engine.scan(&contents)?;
+            if json {
+                return print_json(&tokens);
+            }
 
-        if input.is_empty() {
-            return Ok(());
+            let mut stdout = io::stdout().lock();
+            for token in tokens {
+                writeln!(stdout, "{}", token)?;
+            }
         }
+        Dump::Ast => {
+            let stmts: Vec<Stmt> = engine
+                .parse(&contents)?
+                .into_iter()
+                .filter(|stmt| !matches!(stmt, Stmt::Empty))
+                .collect();
+            if json {
+                return print_json(&stmts);
+            }
 
-        if let Err(err) = run(input) {
-            error!("Error while running code: {:?}", err);
+            let mut stdout = io::stdout().lock();
+            for stmt in &stmts {
+                writeln!(stdout, "{}", ast_printer::print_stmt(stmt))?;
+            }
         }
     }
+
+    Ok(())
 }
 
-pub fn run(source: &str) -> EmpResult {
-    let mut scanner = Scanner::new(source.to_string());
-    let tokens = scanner.scan_tokens();
+/// Formats the given files in place. With `check`, lists the files that aren't formatted
+/// instead, and fails if there are any.
+pub fn format_files(paths: &[PathBuf], check: bool) -> EmpResult {
+    let mut engine = Engine::new();
+    let mut unformatted = Vec::new();
 
-    for token in &tokens {
-        debug!("{token}");
-    }
+    for path in paths {
+        let contents = read_source(path)?;
+        let formatted = engine.format(&contents)?;
+        if formatted == contents {
+            continue;
+        }
 
-    let mut parser = Parser::new(tokens);
-    let stmts = parser.parse();
+        if check {
+            writeln!(io::stdout(), "Would reformat {}", path.display())?;
+            unformatted.push(path.clone());
+        } else {
+            info!("Reformatting {:?}", path);
+            fs::write(path, formatted)?;
+        }
+    }
 
-    if HAD_ERROR.load(Ordering::SeqCst) {
-        return Err("Error while parsing".into());
+    if !unformatted.is_empty() {
+        return Err(formatter::Unformatted(unformatted).into());
     }
 
-    Interpreter::interpret(stmts);
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+fn print_json(value: &impl serde::Serialize) -> EmpResult {
+    writeln!(io::stdout(), "{}", serde_json::to_string_pretty(value)?)?;
+    Ok(())
+}
+
+#[cfg(not(feature = "serde"))]
+fn print_json<T>(_value: &T) -> EmpResult {
+    Err("JSON output requires the `serde` feature".into())
+}
+
+/// Loads statements from their JSON representation.
+#[cfg(feature = "serde")]
+pub fn stmts_from_json(json: &str) -> Result<Vec<Stmt>, Box<dyn std::error::Error>> {
+    Ok(serde_json::from_str(json)?)
+}
+
+#[cfg(not(feature = "serde"))]
+pub fn stmts_from_json(_json: &str) -> Result<Vec<Stmt>, Box<dyn std::error::Error>> {
+    Err("JSON input requires the `serde` feature".into())
+}
+
+pub fn run(source: &str) -> EmpResult {
+    run_with_output(source, io::stdout(), io::stderr())
+}
 
+/// Runs the given source code, writing program output and error messages to the given sinks.
+pub fn run_with_output(
+    source: &str,
+    stdout: impl Write + 'static,
+    stderr: impl Write + 'static,
+) -> EmpResult {
+    let mut engine = Engine::new();
+    engine.set_stdout(stdout);
+    engine.set_stderr(stderr);
+    engine.eval(source)?;
     Ok(())
 }
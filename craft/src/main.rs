@@ -1,58 +1,80 @@
 mod cli;
 
-use craft::{run_file, run_prompt, EmpResult, HAD_ERROR};
-use home::home_dir;
+use cli::Command;
+use craft::{
+    dump_file, exit_code, format_files, run_ast_file, run_file, run_prompt, state_dir, EmpResult,
+    EX_SOFTWARE, EX_USAGE,
+};
 use log::*;
 use simplelog::*;
-use std::{
-    fs::{self, File},
-    sync::atomic::Ordering,
-};
+use std::fs::{self, File};
+use std::process::ExitCode;
 
-/// Initialize logging to a file and stdout
+/// Initialize logging to a file and stderr
 fn init_logging() -> EmpResult {
-    let log_file_path = home_dir()
+    let log_file_path = state_dir()
         .ok_or("Failed to get home directory")?
-        .join(".local")
-        .join("state")
         .join("fun_interpreter.log");
 
-    println!("Logs saved to {log_file_path:?}");
-
     fs::create_dir_all(log_file_path.parent().unwrap())?;
 
     CombinedLogger::init(vec![
+        // Only problems go to the terminal, so that stderr carries just the script's diagnostics.
         TermLogger::new(
-            LevelFilter::Info,
+            LevelFilter::Warn,
             Config::default(),
-            TerminalMode::Mixed,
+            // Keep stdout for the program's own output.
+            TerminalMode::Stderr,
             ColorChoice::Auto,
         ),
         WriteLogger::new(
             LevelFilter::Trace,
             Config::default(),
-            File::create(log_file_path).unwrap(),
+            File::create(&log_file_path).unwrap(),
         ),
     ])?;
 
-    info!("Logging initialized");
+    info!("Logs saved to {log_file_path:?}");
 
     Ok(())
 }
 
-fn main() -> EmpResult {
-    init_logging()?;
-    let args = cli::parse_args();
+fn main() -> ExitCode {
+    let args = match cli::parse_args() {
+        Ok(args) => args,
+        Err(err) => {
+            let _ = err.print();
+            // `--help` and `--version` are reported as errors too, but aren't failures.
+            return if err.use_stderr() {
+                ExitCode::from(EX_USAGE)
+            } else {
+                ExitCode::SUCCESS
+            };
+        }
+    };
 
-    match args.file {
-        Some(path) => run_file(path),
-        None => run_prompt(),
+    if let Err(err) = init_logging() {
+        eprintln!("Failed to initialize logging: {err}");
+        return ExitCode::from(EX_SOFTWARE);
     }
-    .expect("Failed to run interpreter");
 
-    if HAD_ERROR.load(Ordering::SeqCst) {
-        Err("Encountered error(s) while running")?
-    }
+    let dump = args.dump();
+    let result = match (args.command, dump, args.file) {
+        (Some(Command::Fmt { check, files }), _, _) => format_files(&files, check),
+        (_, Some(dump), Some(path)) => dump_file(path, dump, args.json),
+        (_, _, Some(path)) if args.from_ast => run_ast_file(path),
+        (_, _, Some(path)) => run_file(path),
+        (_, _, None) => run_prompt(),
+    };
 
-    Ok(())
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            // Errors from the script itself have already been reported by the interpreter.
+            if err.downcast_ref::<craft::Error>().is_none() {
+                eprintln!("{err}");
+            }
+            ExitCode::from(exit_code(err.as_ref()))
+        }
+    }
 }
@@ -0,0 +1,107 @@
+use crate::ast::Object;
+#[cfg(feature = "bigint")]
+use crate::bigint::{self, BigInt};
+use crate::interpreter::RuntimeError;
+#[cfg(feature = "bigint")]
+use num_traits::FromPrimitive;
+use std::fmt::{Debug, Display};
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Signature of the Rust closure backing a native function.
+pub type NativeFn = dyn Fn(&[Object]) -> Result<Object, RuntimeError>;
+
+/// A function implemented in Rust and callable from Lox code.
+pub struct NativeFunction {
+    pub name: String,
+    pub arity: usize,
+    pub function: Rc<NativeFn>,
+}
+
+impl NativeFunction {
+    pub fn new(
+        name: &str,
+        arity: usize,
+        function: impl Fn(&[Object]) -> Result<Object, RuntimeError> + 'static,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            arity,
+            function: Rc::new(function),
+        }
+    }
+
+    pub fn call(&self, arguments: &[Object]) -> Result<Object, RuntimeError> {
+        (self.function)(arguments)
+    }
+}
+
+impl Debug for NativeFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "NativeFunction({}/{})", self.name, self.arity)
+    }
+}
+
+impl Display for NativeFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<native fn {}>", self.name)
+    }
+}
+
+/// Returns the number of seconds elapsed since the Unix epoch.
+pub fn clock(_arguments: &[Object]) -> Result<Object, RuntimeError> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|err| RuntimeError::message(&err.to_string()))?;
+
+    Ok(Object::Number(now.as_secs_f64()))
+}
+
+/// Converts a number or a string to an integer. Floats are rounded towards zero.
+pub fn int(arguments: &[Object]) -> Result<Object, RuntimeError> {
+    let value = &arguments[0];
+
+    #[cfg(feature = "bigint")]
+    let integer = match value {
+        Object::Int(_) | Object::BigInt(_) => Some(value.clone()),
+        Object::Number(value) => BigInt::from_f64(value.trunc()).map(bigint::normalize),
+        Object::String(value) => value.trim().parse().ok().map(bigint::normalize),
+        _ => None,
+    };
+
+    #[cfg(not(feature = "bigint"))]
+    let integer = match value {
+        Object::Int(value) => Some(*value),
+        Object::Number(value) => truncate(*value),
+        Object::String(value) => value.trim().parse().ok(),
+        _ => None,
+    }
+    .map(Object::Int);
+
+    integer
+        .ok_or_else(|| RuntimeError::message(&format!("Can't convert '{}' to an integer.", value)))
+}
+
+/// Converts a number or a string to a float.
+pub fn float(arguments: &[Object]) -> Result<Object, RuntimeError> {
+    let value = &arguments[0];
+
+    match value {
+        Object::Int(value) => Some(*value as f64),
+        #[cfg(feature = "bigint")]
+        Object::BigInt(value) => Some(bigint::to_f64(value)),
+        Object::Number(value) => Some(*value),
+        Object::String(value) => value.trim().parse().ok(),
+        _ => None,
+    }
+    .map(Object::Number)
+    .ok_or_else(|| RuntimeError::message(&format!("Can't convert '{}' to a float.", value)))
+}
+
+/// Returns the float rounded towards zero, if that fits in an integer.
+pub(crate) fn truncate(value: f64) -> Option<i64> {
+    // 2^63, the first value above the range of `i64`.
+    const LIMIT: f64 = 9_223_372_036_854_775_808.0;
+
+    (-LIMIT..LIMIT).contains(&value).then_some(value as i64)
+}
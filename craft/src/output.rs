@@ -0,0 +1,37 @@
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+/// An in-memory output sink. Clones share the same buffer, so one clone can be handed to the
+/// interpreter while another is used to read back what was written.
+#[derive(Clone, Debug, Default)]
+pub struct CapturedOutput {
+    buffer: Rc<RefCell<Vec<u8>>>,
+}
+
+impl CapturedOutput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns everything written so far.
+    pub fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.buffer.borrow()).into_owned()
+    }
+
+    /// Returns everything written so far, and clears the buffer.
+    pub fn take(&self) -> String {
+        let bytes = self.buffer.take();
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+}
+
+impl Write for CapturedOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
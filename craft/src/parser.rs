@@ -1,26 +1,74 @@
-use std::sync::atomic::Ordering;
-
 use crate::token::{Token, TokenType};
-use crate::{ast::*, Eer, HAD_ERROR};
+use crate::{ast::*, Eer};
 
 /// Parser converts a sequence of tokens produced by the scanner / lexer into a syntax tree (AST).
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    /// Whether a trailing expression without a semicolon is accepted, and printed when run.
+    repl: bool,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        Self { tokens, current: 0 }
+        Self {
+            tokens,
+            current: 0,
+            repl: false,
+        }
+    }
+
+    /// Creates a parser for interactive input, where a bare expression is echoed back.
+    pub fn new_repl(tokens: Vec<Token>) -> Self {
+        Self {
+            repl: true,
+            ..Self::new(tokens)
+        }
     }
 
     pub fn parse(&mut self) -> Vec<Stmt> {
         let mut statements = Vec::new();
         while !self.is_at_end() {
-            statements.push(self.statement());
+            statements.push(self.declaration());
+        }
+
+        statements
+    }
+
+    fn declaration(&mut self) -> Stmt {
+        if self.check(TokenType::VAR) {
+            return self.var_declaration();
+        }
+
+        self.statement()
+    }
+
+    fn var_declaration(&mut self) -> Stmt {
+        self.advance();
+
+        let name = self.peek().clone();
+        if !matches!(name.variant, TokenType::IDENTIFIER(_)) {
+            let _ = self.error(name.clone(), "Expect variable name.");
+            self.synchronize();
+            return Stmt::Empty;
+        }
+        self.advance();
+
+        let mut initializer = None;
+        if self.match_token(vec![TokenType::EQUAL]) {
+            initializer = Some(self.expression());
         }
 
-        return statements;
+        let result = self.consume(
+            TokenType::SEMICOLON,
+            "Expect ';' after variable declaration.",
+        );
+
+        if result.is_err() {
+            self.synchronize();
+        }
+
+        Stmt::Var(Box::new(Var::new(name, initializer)))
     }
 
     fn statement(&mut self) -> Stmt {
@@ -42,28 +90,130 @@ impl Parser {
         if result.is_err() {
             self.synchronize();
         }
-        return Stmt::Print(Box::new(Print::new(value)));
+        Stmt::Print(Box::new(Print::new(value)))
     }
 
     fn expression_statement(&mut self) -> Stmt {
         let expr = self.expression();
+
+        if self.repl && self.check(TokenType::EOF) {
+            return Stmt::Print(Box::new(Print::new(expr)));
+        }
+
         let result = self.consume(TokenType::SEMICOLON, "Expect ';' after expression.");
 
         if result.is_err() {
             self.synchronize();
         }
 
-        return Stmt::Expression(Box::new(Expression::new(expr)));
+        Stmt::Expression(Box::new(Expression::new(expr)))
     }
 
     fn expression(&mut self) -> Expr {
-        self.equality()
+        self.comma()
+    }
+
+    /// Parses the C-style comma operator, which evaluates both operands and gives the right one.
+    fn comma(&mut self) -> Expr {
+        let mut expr: Expr = self.assignment();
+
+        while self.match_token(vec![TokenType::COMMA]) {
+            let right: Expr = self.assignment();
+            expr = Expr::Comma(Box::new(Comma::new(expr, right)));
+        }
+
+        expr
+    }
+
+    fn assignment(&mut self) -> Expr {
+        let expr = self.conditional();
+
+        if self.match_token(vec![TokenType::EQUAL]) {
+            let equals = self.previous().clone();
+            let value = self.assignment();
+
+            if let Expr::Variable(variable) = &expr {
+                return Expr::Assign(Box::new(Assign::new(variable.name.clone(), value)));
+            }
+
+            // Report without synchronizing, the parser is still in a valid state.
+            let _ = self.error(equals, "Invalid assignment target.");
+        }
+
+        expr
+    }
+
+    /// Parses `condition ? then : else`, which is right-associative. Like in C, the branch
+    /// between `?` and `:` can be any expression.
+    fn conditional(&mut self) -> Expr {
+        let condition: Expr = self.equality();
+
+        if self.match_token(vec![TokenType::QUESTION]) {
+            let then_branch: Expr = self.expression();
+            if self
+                .consume(
+                    TokenType::COLON,
+                    "Expect ':' after then branch of conditional expression.",
+                )
+                .is_err()
+            {
+                // Report without synchronizing, so that the statement can still end normally.
+                return condition;
+            }
+            let else_branch: Expr = self.conditional();
+
+            return Expr::Conditional(Box::new(Conditional::new(
+                condition,
+                then_branch,
+                else_branch,
+            )));
+        }
+
+        condition
     }
 
     fn equality(&mut self) -> Expr {
-        let mut expr: Expr = self.comparison();
+        let mut expr: Expr = self.bit_or();
 
         while self.match_token(vec![TokenType::BANG_EQUAL, TokenType::EQUAL_EQUAL]) {
+            let operator: Token = self.previous().clone();
+            let right: Expr = self.bit_or();
+            expr = Expr::Binary(Box::new(Binary::new(expr, operator, right)));
+        }
+
+        expr
+    }
+
+    // The bitwise operators bind tighter than equality, unlike in C, so that `a & b == 0` means
+    // `(a & b) == 0`.
+    fn bit_or(&mut self) -> Expr {
+        let mut expr: Expr = self.bit_xor();
+
+        while self.match_token(vec![TokenType::PIPE]) {
+            let operator: Token = self.previous().clone();
+            let right: Expr = self.bit_xor();
+            expr = Expr::Binary(Box::new(Binary::new(expr, operator, right)));
+        }
+
+        expr
+    }
+
+    fn bit_xor(&mut self) -> Expr {
+        let mut expr: Expr = self.bit_and();
+
+        while self.match_token(vec![TokenType::CARET]) {
+            let operator: Token = self.previous().clone();
+            let right: Expr = self.bit_and();
+            expr = Expr::Binary(Box::new(Binary::new(expr, operator, right)));
+        }
+
+        expr
+    }
+
+    fn bit_and(&mut self) -> Expr {
+        let mut expr: Expr = self.comparison();
+
+        while self.match_token(vec![TokenType::AMPERSAND]) {
             let operator: Token = self.previous().clone();
             let right: Expr = self.comparison();
             expr = Expr::Binary(Box::new(Binary::new(expr, operator, right)));
@@ -73,7 +223,7 @@ impl Parser {
     }
 
     fn comparison(&mut self) -> Expr {
-        let mut expr: Expr = self.term();
+        let mut expr: Expr = self.shift();
 
         while self.match_token(vec![
             TokenType::GREATER,
@@ -81,6 +231,18 @@ impl Parser {
             TokenType::LESS,
             TokenType::LESS_EQUAL,
         ]) {
+            let operator: Token = self.previous().clone();
+            let right: Expr = self.shift();
+            expr = Expr::Binary(Box::new(Binary::new(expr, operator, right)));
+        }
+
+        expr
+    }
+
+    fn shift(&mut self) -> Expr {
+        let mut expr: Expr = self.term();
+
+        while self.match_token(vec![TokenType::LESS_LESS, TokenType::GREATER_GREATER]) {
             let operator: Token = self.previous().clone();
             let right: Expr = self.term();
             expr = Expr::Binary(Box::new(Binary::new(expr, operator, right)));
@@ -104,7 +266,12 @@ impl Parser {
     fn factor(&mut self) -> Expr {
         let mut expr: Expr = self.unary();
 
-        while self.match_token(vec![TokenType::SLASH, TokenType::STAR]) {
+        while self.match_token(vec![
+            TokenType::SLASH,
+            TokenType::STAR,
+            TokenType::PERCENT,
+            TokenType::TILDE_SLASH,
+        ]) {
             let operator: Token = self.previous().clone();
             let right: Expr = self.unary();
             expr = Expr::Binary(Box::new(Binary::new(expr, operator, right)));
@@ -114,13 +281,66 @@ impl Parser {
     }
 
     fn unary(&mut self) -> Expr {
-        if self.match_token(vec![TokenType::BANG, TokenType::MINUS]) {
+        if self.match_token(vec![TokenType::BANG, TokenType::MINUS, TokenType::TILDE]) {
             let operator: Token = self.previous().clone();
             let right: Expr = self.unary();
             return Expr::Unary(Box::new(Unary::new(operator, right)));
         }
 
-        self.primary()
+        self.power()
+    }
+
+    /// Parses `**`, which binds tighter than a prefix operator on its left, so `-2 ** 2` is `-4`,
+    /// and is right-associative.
+    fn power(&mut self) -> Expr {
+        let expr: Expr = self.call();
+
+        if self.match_token(vec![TokenType::STAR_STAR]) {
+            let operator: Token = self.previous().clone();
+            let right: Expr = self.unary();
+            return Expr::Binary(Box::new(Binary::new(expr, operator, right)));
+        }
+
+        expr
+    }
+
+    fn call(&mut self) -> Expr {
+        let mut expr: Expr = self.primary();
+
+        while self.match_token(vec![TokenType::LEFT_PAREN]) {
+            expr = self.finish_call(expr);
+        }
+
+        expr
+    }
+
+    fn finish_call(&mut self, callee: Expr) -> Expr {
+        let mut arguments = Vec::new();
+
+        if !self.check(TokenType::RIGHT_PAREN) {
+            loop {
+                if arguments.len() >= 255 {
+                    // Report without synchronizing, the parser is still in a valid state.
+                    let _ = self.error(self.peek().clone(), "Can't have more than 255 arguments.");
+                }
+                // Commas separate the arguments, so they can't be comma expressions.
+                arguments.push(self.assignment());
+
+                if !self.match_token(vec![TokenType::COMMA]) {
+                    break;
+                }
+            }
+        }
+
+        let paren = self.peek().clone();
+        if self
+            .consume(TokenType::RIGHT_PAREN, "Expect ')' after arguments.")
+            .is_err()
+        {
+            self.synchronize();
+        }
+
+        Expr::Call(Box::new(Call::new(callee, paren, arguments)))
     }
 
     fn primary(&mut self) -> Expr {
@@ -145,10 +365,26 @@ impl Parser {
                 self.advance();
                 expr = Expr::Literal(Box::new(Literal::new(Object::Number(*num))));
             }
+            TokenType::INTEGER(num) => {
+                self.advance();
+                expr = Expr::Literal(Box::new(Literal::new(Object::Int(*num))));
+            }
+            #[cfg(feature = "bigint")]
+            TokenType::BIG_INTEGER(num) => {
+                self.advance();
+                expr = Expr::Literal(Box::new(Literal::new(Object::BigInt(num.clone()))));
+            }
             TokenType::STRING(str) => {
                 self.advance();
                 expr = Expr::Literal(Box::new(Literal::new(Object::String(str.clone()))));
             }
+            TokenType::IDENTIFIER(_) => {
+                self.advance();
+                expr = Expr::Variable(Box::new(Variable::new(token)));
+            }
+            TokenType::INTERPOLATION(_) => {
+                expr = self.interpolation();
+            }
             TokenType::LEFT_PAREN => {
                 self.advance();
                 expr = self.expression();
@@ -159,7 +395,16 @@ impl Parser {
                 expr = Expr::Grouping(Box::new(Grouping::new(expr)));
             }
             _ => {
-                panic!("Unexpected token: {:?}", token);
+                expr = Expr::Literal(Box::new(Literal::new(Object::Nil)));
+
+                if !self.missing_left_operand(&token) {
+                    // Report without synchronizing, and skip the token unless it ends the
+                    // statement, so that the statement can still end normally.
+                    let _ = self.error(token.clone(), "Expect expression.");
+                    if !matches!(token.variant, TokenType::SEMICOLON | TokenType::EOF) {
+                        self.advance();
+                    }
+                }
             }
         };
 
@@ -170,8 +415,74 @@ impl Parser {
         expr
     }
 
+    /// Parses a binary operator at the start of an expression, which is missing its left operand,
+    /// as an error production. The right operand is parsed at the operator's precedence and
+    /// discarded. Returns whether the token was a binary operator.
+    fn missing_left_operand(&mut self, operator: &Token) -> bool {
+        let right_operand: fn(&mut Self) -> Expr = match operator.variant {
+            TokenType::COMMA => Self::assignment,
+            TokenType::BANG_EQUAL | TokenType::EQUAL_EQUAL => Self::bit_or,
+            TokenType::PIPE => Self::bit_xor,
+            TokenType::CARET => Self::bit_and,
+            TokenType::AMPERSAND => Self::comparison,
+            TokenType::GREATER
+            | TokenType::GREATER_EQUAL
+            | TokenType::LESS
+            | TokenType::LESS_EQUAL => Self::shift,
+            TokenType::LESS_LESS | TokenType::GREATER_GREATER => Self::term,
+            TokenType::PLUS => Self::factor,
+            TokenType::SLASH
+            | TokenType::STAR
+            | TokenType::PERCENT
+            | TokenType::TILDE_SLASH
+            | TokenType::STAR_STAR => Self::unary,
+            _ => return false,
+        };
+
+        // Report without synchronizing, the parser is still in a valid state.
+        let _ = self.error(operator.clone(), "Missing left-hand operand.");
+        self.advance();
+        right_operand(self);
+
+        true
+    }
+
+    /// Parses an interpolated string, whose tokens are the segments before each `${` and the
+    /// expressions after them, up to the `STRING` token that ends it.
+    fn interpolation(&mut self) -> Expr {
+        let mut parts = Vec::new();
+
+        while let TokenType::INTERPOLATION(segment) = &self.peek().variant {
+            parts.push(Expr::Literal(Box::new(Literal::new(Object::String(
+                segment.clone(),
+            )))));
+            self.advance();
+            parts.push(self.expression());
+        }
+
+        let segment = match &self.peek().variant {
+            TokenType::STRING(segment) => {
+                let segment = segment.clone();
+                self.advance();
+                segment
+            }
+            _ => {
+                // Leave the token for the enclosing statement to recover from.
+                let _ = self.error(
+                    self.peek().clone(),
+                    "Expect '}' after interpolated expression.",
+                );
+                String::new()
+            }
+        };
+        parts.push(Expr::Literal(Box::new(Literal::new(Object::String(
+            segment,
+        )))));
+
+        Expr::Interpolation(Box::new(Interpolation::new(parts)))
+    }
+
     fn synchronize(&mut self) {
-        HAD_ERROR.store(true, Ordering::SeqCst);
         self.advance();
         while !self.is_at_end() {
             if self.previous().variant == TokenType::SEMICOLON {
@@ -241,7 +552,7 @@ impl Parser {
     }
 
     fn error(&self, token: Token, message: &str) -> Eer {
-        crate::error(token.line, message);
+        crate::token_error(&token, message);
         Err(())
     }
 }
@@ -0,0 +1,277 @@
+use crate::ast_printer::print_stmt;
+use crate::scanner::{Scanner, KEYWORDS};
+use crate::token::TokenType;
+use crate::{state_dir, take_diagnostics, EmpResult, Engine};
+use log::*;
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::FileHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use std::fs;
+use std::time::Instant;
+use unicode_ident::is_xid_continue;
+
+const PROMPT: &str = "> ";
+const CONTINUATION_PROMPT: &str = ".. ";
+const HISTORY_FILE: &str = "fun_interpreter_history";
+
+const COMMANDS: [&str; 7] = [
+    ":help", ":load", ":tokens", ":ast", ":env", ":reset", ":time",
+];
+
+const HELP: &str = "\
+Commands:
+  :help           Show this message
+  :load <file>    Run a file in the current session
+  :tokens <code>  Show the tokens scanned from the code
+  :ast <code>     Show the syntax tree parsed from the code
+  :env            List the global variables
+  :reset          Forget everything defined in this session
+  :time <code>    Run the code and show how long it took";
+
+type ReplEditor = Editor<ReplHelper, FileHistory>;
+
+/// Offers Tab completion of keywords, commands and the names of globals.
+#[derive(Default)]
+pub struct ReplHelper {
+    /// Names bound in the session's global environment.
+    pub globals: Vec<String>,
+}
+
+impl ReplHelper {
+    /// Returns where the word under the cursor starts, and the ways it can be completed.
+    pub fn candidates(&self, line: &str, pos: usize) -> (usize, Vec<String>) {
+        let start = line[..pos]
+            .char_indices()
+            .rev()
+            .find(|(_, c)| !is_xid_continue(*c))
+            .map_or(0, |(index, c)| index + c.len_utf8());
+        let word = &line[start..pos];
+        let before = line[..start].trim_end();
+
+        let mut candidates: Vec<String> = if before.is_empty() && word.is_empty() {
+            Vec::new()
+        } else if before == ":" {
+            COMMANDS
+                .iter()
+                .filter(|command| command[1..].starts_with(word))
+                .map(|command| command[1..].to_string())
+                .collect()
+        } else if before.ends_with('.') {
+            // Properties can't be completed until the language has classes.
+            Vec::new()
+        } else {
+            KEYWORDS
+                .keys()
+                .chain(&self.globals)
+                .filter(|name| name.starts_with(word))
+                .cloned()
+                .collect()
+        };
+
+        candidates.sort();
+        candidates.dedup();
+        (start, candidates)
+    }
+}
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(self.candidates(line, pos))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+pub fn run_prompt() -> EmpResult {
+    println!("REPL mode: Type code to run");
+
+    let mut editor = ReplEditor::new()?;
+    editor.set_helper(Some(ReplHelper::default()));
+    let history_path = state_dir().map(|dir| dir.join(HISTORY_FILE));
+    if let Some(path) = &history_path {
+        // There is no history file before the first session.
+        if let Err(err) = editor.load_history(path) {
+            debug!("Could not load REPL history from {:?}: {}", path, err);
+        }
+    }
+
+    // A single engine is used for the whole session, so that definitions carry over between lines.
+    let mut engine = Engine::new();
+    while let Some(input) = read_input(&mut editor)? {
+        if input.trim().is_empty() {
+            continue;
+        }
+
+        editor.add_history_entry(input.trim_end())?;
+
+        if input.trim_start().starts_with(':') {
+            run_command(&mut engine, input.trim());
+        } else {
+            eval(&mut engine, &input);
+        }
+
+        if let Some(helper) = editor.helper_mut() {
+            helper.globals = engine
+                .globals()
+                .iter()
+                .map(|(name, _)| name.clone())
+                .collect();
+        }
+    }
+
+    if let Some(path) = &history_path {
+        if let Err(err) = editor.save_history(path) {
+            warn!("Could not save REPL history to {:?}: {}", path, err);
+        }
+    }
+
+    Ok(())
+}
+
+fn eval(engine: &mut Engine, input: &str) {
+    // Errors have already been reported by the engine.
+    if let Err(err) = engine.eval_repl(input) {
+        debug!("Error while running code: {:?}", err);
+    }
+}
+
+/// Runs a `:` command.
+fn run_command(engine: &mut Engine, input: &str) {
+    let (command, argument) = match input.split_once(char::is_whitespace) {
+        Some((command, argument)) => (command, argument.trim()),
+        None => (input, ""),
+    };
+
+    let needs_argument = matches!(command, ":load" | ":tokens" | ":ast" | ":time");
+    if needs_argument && argument.is_empty() {
+        eprintln!("{} needs an argument. Type :help for usage.", command);
+        return;
+    }
+
+    match command {
+        ":help" => println!("{}", HELP),
+        ":load" => match fs::read_to_string(argument) {
+            Ok(source) => {
+                if let Err(err) = engine.eval(&source) {
+                    debug!("Error while running {}: {:?}", argument, err);
+                }
+            }
+            Err(err) => eprintln!("Could not read {}: {}", argument, err),
+        },
+        ":tokens" => {
+            for token in Scanner::new(argument.to_string()).scan_tokens() {
+                println!("{}", token);
+            }
+            for diagnostic in take_diagnostics() {
+                eprintln!("{}", diagnostic);
+            }
+        }
+        ":ast" => {
+            // Errors have already been reported by the engine.
+            if let Ok(stmts) = engine.parse_repl(argument) {
+                for stmt in stmts.iter().map(print_stmt).filter(|s| !s.is_empty()) {
+                    println!("{}", stmt);
+                }
+            }
+        }
+        ":env" => {
+            let mut globals: Vec<_> = engine.globals().iter().collect();
+            globals.sort_by(|a, b| a.0.cmp(b.0));
+            for (name, value) in globals {
+                println!("{} = {}", name, value);
+            }
+        }
+        ":reset" => *engine = Engine::new(),
+        ":time" => {
+            let start = Instant::now();
+            eval(engine, argument);
+            println!("Took {:?}", start.elapsed());
+        }
+        _ => eprintln!(
+            "Unknown command {}. Type :help for a list of commands.",
+            command
+        ),
+    }
+}
+
+/// Reads a complete piece of input, prompting for more lines while brackets are unbalanced or a
+/// string or block comment is unterminated. Returns `None` at the end of input.
+fn read_input(editor: &mut ReplEditor) -> Result<Option<String>, ReadlineError> {
+    let mut input = String::new();
+
+    loop {
+        let prompt = if input.is_empty() {
+            PROMPT
+        } else {
+            CONTINUATION_PROMPT
+        };
+
+        match editor.readline(prompt) {
+            Ok(line) => {
+                input.push_str(&line);
+                input.push('\n');
+
+                if is_complete(&input) {
+                    return Ok(Some(input));
+                }
+            }
+            // Ctrl-C discards the input typed so far.
+            Err(ReadlineError::Interrupted) => input.clear(),
+            // Run whatever is left, so that its errors get reported.
+            Err(ReadlineError::Eof) if !input.is_empty() => return Ok(Some(input)),
+            Err(ReadlineError::Eof) => return Ok(None),
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Returns whether the input can be run, or needs more lines to be complete. The input is
+/// scanned as it would be when run, so that strings and comments are recognized the same way.
+fn is_complete(input: &str) -> bool {
+    let tokens = Scanner::new(input.to_string()).scan_tokens();
+    // The errors are reported again when the input is run.
+    let errors = take_diagnostics();
+
+    const UNTERMINATED: [&str; 3] = [
+        "Unterminated string.",
+        "Unterminated block comment.",
+        "Unterminated string interpolation.",
+    ];
+    if errors
+        .iter()
+        .any(|error| UNTERMINATED.iter().any(|message| error.ends_with(message)))
+    {
+        return false;
+    }
+
+    let depth: i32 = tokens
+        .iter()
+        .map(|token| match token.variant {
+            TokenType::LEFT_PAREN | TokenType::LEFT_BRACE => 1,
+            TokenType::RIGHT_PAREN | TokenType::RIGHT_BRACE => -1,
+            _ => 0,
+        })
+        .sum();
+
+    // Extra closing brackets are left for the parser to report.
+    depth <= 0
+}
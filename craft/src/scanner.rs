@@ -1,12 +1,14 @@
-use log::debug;
-
+#[cfg(feature = "bigint")]
+use crate::bigint::BigInt;
 use crate::error;
 use crate::token::Token;
 use crate::token_type::TokenType;
 use std::collections::HashMap;
+use unicode_ident::{is_xid_continue, is_xid_start};
+use unicode_normalization::UnicodeNormalization;
 
 lazy_static! {
-    static ref KEYWORDS: HashMap<String, TokenType> = {
+    pub(crate) static ref KEYWORDS: HashMap<String, TokenType> = {
         let mut map = HashMap::new();
         map.insert("and".to_string(), TokenType::AND);
         map.insert("class".to_string(), TokenType::CLASS);
@@ -29,21 +31,35 @@ lazy_static! {
 }
 
 pub struct Scanner {
-    source: String,
+    source: Vec<char>,
     tokens: Vec<Token>,
     start: usize,
     current: usize,
     line: usize,
+    /// Whether to keep comments as `COMMENT` tokens instead of dropping them.
+    trivia: bool,
+    /// For each `${` of an interpolated string being scanned, the number of `{`s opened since.
+    interpolations: Vec<usize>,
 }
 
 impl Scanner {
     pub fn new(source: String) -> Self {
         Self {
-            source,
+            source: source.chars().collect(),
             tokens: Vec::new(),
             start: 0,
             current: 0,
             line: 1,
+            trivia: false,
+            interpolations: Vec::new(),
+        }
+    }
+
+    /// Creates a scanner that keeps comments, for tools that need to reproduce the source.
+    pub fn with_trivia(source: String) -> Self {
+        Self {
+            trivia: true,
+            ..Self::new(source)
         }
     }
 
@@ -54,6 +70,10 @@ impl Scanner {
             self.scan_token();
         }
 
+        if !self.interpolations.is_empty() {
+            error(self.line, "Unterminated string interpolation.");
+        }
+
         self.tokens
             .push(Token::new(TokenType::EOF, "~EOF".to_string(), self.line));
 
@@ -65,15 +85,66 @@ impl Scanner {
         match c {
             '(' => self.add_token(TokenType::LEFT_PAREN),
             ')' => self.add_token(TokenType::RIGHT_PAREN),
-            '{' => self.add_token(TokenType::LEFT_BRACE),
-            '}' => self.add_token(TokenType::RIGHT_BRACE),
+            '{' => {
+                if let Some(braces) = self.interpolations.last_mut() {
+                    *braces += 1;
+                }
+                self.add_token(TokenType::LEFT_BRACE)
+            }
+            '}' => {
+                if self.interpolations.last() == Some(&0) {
+                    // The end of an interpolated expression, where its string carries on.
+                    self.interpolations.pop();
+                    self.string();
+                } else {
+                    if let Some(braces) = self.interpolations.last_mut() {
+                        *braces -= 1;
+                    }
+                    self.add_token(TokenType::RIGHT_BRACE)
+                }
+            }
             ',' => self.add_token(TokenType::COMMA),
             '.' => self.add_token(TokenType::DOT),
             '-' => self.add_token(TokenType::MINUS),
             '+' => self.add_token(TokenType::PLUS),
             ';' => self.add_token(TokenType::SEMICOLON),
-            '/' => self.add_token(TokenType::SLASH),
-            '*' => self.add_token(TokenType::STAR),
+            '?' => self.add_token(TokenType::QUESTION),
+            ':' => self.add_token(TokenType::COLON),
+            '/' => {
+                if self.check_and_use_next('/') {
+                    // A comment goes until the end of the line.
+                    while self.peek() != '\n' && !self.is_at_end() {
+                        self.advance();
+                    }
+
+                    if self.trivia {
+                        self.add_token(TokenType::COMMENT);
+                    }
+                } else if self.check_and_use_next('*') {
+                    self.block_comment();
+                } else {
+                    self.add_token(TokenType::SLASH)
+                }
+            }
+            '*' => {
+                if self.check_and_use_next('*') {
+                    self.add_token(TokenType::STAR_STAR)
+                } else {
+                    self.add_token(TokenType::STAR)
+                }
+            }
+            '%' => self.add_token(TokenType::PERCENT),
+            '&' => self.add_token(TokenType::AMPERSAND),
+            '|' => self.add_token(TokenType::PIPE),
+            '^' => self.add_token(TokenType::CARET),
+            '~' => {
+                // Integer division, written as in Dart since `//` starts a comment.
+                if self.check_and_use_next('/') {
+                    self.add_token(TokenType::TILDE_SLASH)
+                } else {
+                    self.add_token(TokenType::TILDE)
+                }
+            }
             '!' => {
                 if self.check_and_use_next('=') {
                     self.add_token(TokenType::BANG_EQUAL)
@@ -91,6 +162,8 @@ impl Scanner {
             '<' => {
                 if self.check_and_use_next('=') {
                     self.add_token(TokenType::LESS_EQUAL)
+                } else if self.check_and_use_next('<') {
+                    self.add_token(TokenType::LESS_LESS)
                 } else {
                     self.add_token(TokenType::LESS)
                 }
@@ -98,6 +171,8 @@ impl Scanner {
             '>' => {
                 if self.check_and_use_next('=') {
                     self.add_token(TokenType::GREATER_EQUAL)
+                } else if self.check_and_use_next('>') {
+                    self.add_token(TokenType::GREATER_GREATER)
                 } else {
                     self.add_token(TokenType::GREATER)
                 }
@@ -108,46 +183,55 @@ impl Scanner {
             c => {
                 if self.is_digit(c) {
                     self.number();
-                } else if c.is_ascii_alphabetic() {
+                } else if c == 'r' && matches!(self.peek(), '"' | '#') {
+                    self.raw_string();
+                } else if c == '_' || is_xid_start(c) {
                     self.identifier();
                 } else {
-                    error(self.line, &format!("Unexpected character {c}"))
+                    error(self.line, "Unexpected character.")
                 }
             }
         }
     }
 
     fn add_token(&mut self, variant: TokenType) {
-        let text = self.source[self.start..self.current].to_string();
+        let text = self.text(self.start, self.current);
         self.tokens.push(Token::new(variant, text, self.line));
     }
 
+    /// Returns the source code between the given character positions.
+    fn text(&self, start: usize, end: usize) -> String {
+        self.source[start..end].iter().collect()
+    }
+
     fn advance(&mut self) -> char {
-        let value = self.source.chars().nth(self.current);
+        let value = self.source.get(self.current).copied();
 
         if let Some(c) = value {
             self.current += 1;
             c
         } else {
             panic!(
-                "Currenly at {} (char {}), length is {}, tried to advance",
+                "Currenly at {}, length is {}, tried to advance",
                 self.current,
-                self.source.chars().nth(self.current).unwrap_or('\0'),
                 self.source.len()
             );
         }
     }
 
     fn peek(&self) -> char {
-        self.source.chars().nth(self.current).unwrap_or('\0')
+        self.source.get(self.current).copied().unwrap_or('\0')
     }
 
     fn peek2(&self) -> char {
-        if self.current + 1 >= self.source.len() {
-            return '\0';
-        }
+        self.source.get(self.current + 1).copied().unwrap_or('\0')
+    }
 
-        self.source.chars().nth(self.current + 1).unwrap()
+    /// Returns whether the source continues with the given text.
+    fn check_text(&self, text: &str) -> bool {
+        text.chars()
+            .enumerate()
+            .all(|(offset, c)| self.source.get(self.current + offset) == Some(&c))
     }
 
     fn is_at_end(&self) -> bool {
@@ -155,11 +239,7 @@ impl Scanner {
     }
 
     fn check_and_use_next(&mut self, arg: char) -> bool {
-        if self.is_at_end() {
-            return false;
-        }
-
-        if self.source.chars().nth(self.current).unwrap() != arg {
+        if self.is_at_end() || self.peek() != arg {
             return false;
         }
 
@@ -167,60 +247,289 @@ impl Scanner {
         true
     }
 
+    /// Skips a `/* ... */` comment, which may contain other block comments.
+    fn block_comment(&mut self) {
+        let start_line = self.line;
+        let mut depth = 1;
+
+        while depth > 0 {
+            if self.is_at_end() {
+                error(start_line, "Unterminated block comment.");
+                return;
+            }
+
+            match self.advance() {
+                '/' if self.check_and_use_next('*') => depth += 1,
+                '*' if self.check_and_use_next('/') => depth -= 1,
+                '\n' => self.line += 1,
+                _ => (),
+            }
+        }
+
+        if self.trivia {
+            self.add_token(TokenType::COMMENT);
+        }
+    }
+
+    /// Scans a string literal, decoding its escape sequences. A `${` inside the string ends the
+    /// segment scanned so far with an `INTERPOLATION` token, and the string carries on after the
+    /// `}` that matches it.
     fn string(&mut self) {
+        let mut value = String::new();
+
         while self.peek() != '"' && !self.is_at_end() {
-            if self.peek() == '\n' {
-                self.line += 1;
+            match self.advance() {
+                '\\' => {
+                    if let Some(c) = self.escape() {
+                        value.push(c);
+                    }
+                }
+                '$' if self.check_and_use_next('{') => {
+                    self.interpolations.push(0);
+                    self.add_token(TokenType::INTERPOLATION(value));
+                    return;
+                }
+                c => {
+                    if c == '\n' {
+                        self.line += 1;
+                    }
+                    value.push(c);
+                }
             }
-            self.advance();
         }
 
-        debug!("peek: {}", self.peek());
-
-        if self.is_at_end() || self.peek() != '"' {
-            error(self.line, "Unterminated string literal");
+        if self.is_at_end() {
+            error(self.line, "Unterminated string.");
             return;
         }
 
         self.advance(); // The closing ".
-
-        let value = self.source[self.start + 1..self.current - 1].to_string();
         self.add_token(TokenType::STRING(value))
     }
 
+    /// Decodes the escape sequence after a `\`, reporting it if it's invalid.
+    fn escape(&mut self) -> Option<char> {
+        // An unterminated string is reported by the caller.
+        if self.is_at_end() {
+            return None;
+        }
+
+        match self.advance() {
+            'n' => Some('\n'),
+            't' => Some('\t'),
+            '\\' => Some('\\'),
+            '"' => Some('"'),
+            '$' => Some('$'),
+            'u' => self.unicode_escape(),
+            c => {
+                if c == '\n' {
+                    self.line += 1;
+                }
+                error(
+                    self.line,
+                    &format!("Invalid escape sequence '\\{}'.", c.escape_default()),
+                );
+                None
+            }
+        }
+    }
+
+    /// Decodes a `\u{...}` escape, after the `u`.
+    fn unicode_escape(&mut self) -> Option<char> {
+        if !self.check_and_use_next('{') {
+            error(self.line, "Expect '{' after '\\u'.");
+            return None;
+        }
+
+        let mut digits = String::new();
+        while self.peek().is_ascii_hexdigit() {
+            digits.push(self.advance());
+        }
+
+        if !self.check_and_use_next('}') {
+            error(self.line, "Expect hex digits and '}' in Unicode escape.");
+            return None;
+        }
+
+        if digits.is_empty() || digits.len() > 6 {
+            error(self.line, "Unicode escape must have 1 to 6 hex digits.");
+            return None;
+        }
+
+        let c = u32::from_str_radix(&digits, 16)
+            .ok()
+            .and_then(char::from_u32);
+        if c.is_none() {
+            error(
+                self.line,
+                &format!("'\\u{{{}}}' is not a valid Unicode character.", digits),
+            );
+        }
+        c
+    }
+
+    /// Scans a raw string such as `r"C:\path"`, whose contents are kept as they are. Any number of
+    /// `#`s can surround the quotes, so that `r#"say "hi""#` can contain quotes.
+    fn raw_string(&mut self) {
+        let mut hashes = 0;
+        while self.check_and_use_next('#') {
+            hashes += 1;
+        }
+
+        if !self.check_and_use_next('"') {
+            error(self.line, "Expect '\"' after 'r' and '#'s of a raw string.");
+            return;
+        }
+
+        let closing = format!("\"{}", "#".repeat(hashes));
+        let contents_start = self.current;
+        while !self.check_text(&closing) {
+            if self.is_at_end() {
+                error(self.line, "Unterminated string.");
+                return;
+            }
+
+            if self.advance() == '\n' {
+                self.line += 1;
+            }
+        }
+
+        let value = self.text(contents_start, self.current);
+        self.current += closing.len();
+        self.add_token(TokenType::STRING(value));
+    }
+
     fn is_digit(&self, c: char) -> bool {
         c.is_ascii_digit()
     }
 
+    /// Scans a number literal: a decimal with an optional fraction and exponent, or a hexadecimal
+    /// (`0xFF`) or binary (`0b1010`) integer. Digits can be separated with `_`. Literals with a
+    /// fraction or an exponent are floats, and the rest are integers.
     fn number(&mut self) {
-        while self.is_digit(self.peek()) {
-            self.advance();
+        let first = self.source[self.start];
+        let radix = match self.peek() {
+            'x' | 'X' if first == '0' => 16,
+            'b' | 'B' if first == '0' => 2,
+            _ => 10,
+        };
+
+        if radix == 10 {
+            self.digits();
+
+            if self.peek() == '.' && self.is_digit(self.peek2()) {
+                self.advance(); // The ".".
+                self.digits();
+            }
+
+            if matches!(self.peek(), 'e' | 'E') {
+                self.advance();
+                if matches!(self.peek(), '+' | '-') {
+                    self.advance();
+                }
+                self.digits();
+            }
+        } else {
+            self.advance(); // The "x" or "b".
         }
 
-        if self.peek() == ' ' && self.is_digit(self.peek2()) {
+        // Letters right after a number are a malformed literal rather than an identifier, as are
+        // the digits of a hexadecimal or binary literal.
+        while is_xid_continue(self.peek()) {
             self.advance();
         }
 
-        while self.is_digit(self.peek()) {
-            self.advance();
+        let text = self.text(self.start, self.current);
+        let token_type = if radix != 10 {
+            parse_integer(&text[2..], radix)
+        } else if text.contains(['.', 'e', 'E']) {
+            parse_decimal(&text).map(TokenType::NUMBER)
+        } else {
+            parse_integer(&text, radix)
+        };
+
+        match token_type {
+            Ok(token_type) => self.add_token(token_type),
+            Err(message) => {
+                error(self.line, message);
+                // Keep the token, so that the parser doesn't report the literal as missing too.
+                self.add_token(TokenType::INTEGER(0));
+            }
         }
+    }
 
-        self.add_token(TokenType::NUMBER(
-            self.source[self.start..self.current].parse().unwrap(),
-        ));
+    /// Consumes decimal digits and the `_` separators between them.
+    fn digits(&mut self) {
+        while self.is_digit(self.peek()) || self.peek() == '_' {
+            self.advance();
+        }
     }
 
+    /// Scans an identifier or keyword. Identifiers follow Unicode's UAX #31, and are normalized to
+    /// NFC so that names that look the same are the same.
     fn identifier(&mut self) {
-        while self.peek().is_alphanumeric() && !self.peek().is_whitespace() {
+        while is_xid_continue(self.peek()) {
             self.advance();
         }
 
-        let text = self.source[self.start..self.current].to_string();
+        let text: String = self.text(self.start, self.current).nfc().collect();
         let token_type = KEYWORDS
             .get(&text)
             .cloned()
-            .unwrap_or(TokenType::IDENTIFIER(text));
+            .unwrap_or(TokenType::IDENTIFIER(text.clone()));
+
+        self.tokens.push(Token::new(token_type, text, self.line));
+    }
+}
+
+/// Parses a decimal literal, rejecting anything else the scanner consumed with it, and values
+/// too large to be anything but infinity.
+fn parse_decimal(text: &str) -> Result<f64, &'static str> {
+    let value: f64 = remove_separators(text, 10)?
+        .parse()
+        .map_err(|_| "Invalid number literal.")?;
+
+    if value.is_finite() {
+        Ok(value)
+    } else {
+        Err("Number literal is too large.")
+    }
+}
+
+/// Parses the digits of an integer literal in the given base.
+fn parse_integer(text: &str, radix: u32) -> Result<TokenType, &'static str> {
+    let digits = remove_separators(text, radix)?;
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return Err("Invalid number literal.");
+    }
 
-        self.add_token(token_type);
+    // The digits are valid, so parsing can only fail because the value is too large.
+    match i64::from_str_radix(&digits, radix) {
+        Ok(value) => Ok(TokenType::INTEGER(value)),
+        #[cfg(feature = "bigint")]
+        Err(_) => BigInt::parse_bytes(digits.as_bytes(), radix)
+            .map(TokenType::BIG_INTEGER)
+            .ok_or("Invalid number literal."),
+        #[cfg(not(feature = "bigint"))]
+        Err(_) => Err("Number literal is too large."),
     }
 }
+
+/// Removes the `_` separators from a literal, checking that each one is between two digits.
+fn remove_separators(text: &str, radix: u32) -> Result<String, &'static str> {
+    let chars: Vec<char> = text.chars().collect();
+
+    for (index, &c) in chars.iter().enumerate() {
+        let between_digits = index > 0
+            && chars[index - 1].is_digit(radix)
+            && chars
+                .get(index + 1)
+                .is_some_and(|next| next.is_digit(radix));
+
+        if c == '_' && !between_digits {
+            return Err("Invalid number literal.");
+        }
+    }
+
+    Ok(text.replace('_', ""))
+}
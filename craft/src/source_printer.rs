@@ -0,0 +1,173 @@
+use crate::ast::*;
+
+/// Prints the AST back as canonically formatted Lox source code.
+struct SourcePrinter;
+
+/// Returns Lox source code for the given statements, one statement per line.
+pub fn print_source(stmts: &[Stmt]) -> String {
+    let mut printer = SourcePrinter {};
+    let mut retval = String::new();
+
+    for stmt in stmts {
+        let line = printer.visit_stmt(stmt);
+        if !line.is_empty() {
+            retval.push_str(&line);
+            retval.push('\n');
+        }
+    }
+
+    retval
+}
+
+impl Visitor<String> for SourcePrinter {
+    fn visit_expr(&mut self, expr: &Expr) -> String {
+        match expr {
+            Expr::Assign(expr) => self.visit_assign_expr(expr),
+            Expr::Binary(expr) => self.visit_binary_expr(expr),
+            Expr::Call(expr) => self.visit_call_expr(expr),
+            Expr::Comma(expr) => self.visit_comma_expr(expr),
+            Expr::Conditional(expr) => self.visit_conditional_expr(expr),
+            Expr::Grouping(expr) => self.visit_grouping_expr(expr),
+            Expr::Interpolation(expr) => self.visit_interpolation_expr(expr),
+            Expr::Literal(expr) => self.visit_literal_expr(expr),
+            Expr::Unary(expr) => self.visit_unary_expr(expr),
+            Expr::Variable(expr) => self.visit_variable_expr(expr),
+        }
+    }
+
+    fn visit_assign_expr(&mut self, expr: &Assign) -> String {
+        format!("{} = {}", expr.name.lexeme, self.visit_expr(&expr.value))
+    }
+
+    fn visit_binary_expr(&mut self, expr: &Binary) -> String {
+        format!(
+            "{} {} {}",
+            self.visit_expr(&expr.left),
+            expr.operator.lexeme,
+            self.visit_expr(&expr.right)
+        )
+    }
+
+    fn visit_call_expr(&mut self, expr: &Call) -> String {
+        let arguments: Vec<String> = expr
+            .arguments
+            .iter()
+            .map(|argument| self.visit_expr(argument))
+            .collect();
+
+        format!(
+            "{}({})",
+            self.visit_expr(&expr.callee),
+            arguments.join(", ")
+        )
+    }
+
+    fn visit_comma_expr(&mut self, expr: &Comma) -> String {
+        format!(
+            "{}, {}",
+            self.visit_expr(&expr.left),
+            self.visit_expr(&expr.right)
+        )
+    }
+
+    fn visit_conditional_expr(&mut self, expr: &Conditional) -> String {
+        format!(
+            "{} ? {} : {}",
+            self.visit_expr(&expr.condition),
+            self.visit_expr(&expr.then_branch),
+            self.visit_expr(&expr.else_branch)
+        )
+    }
+
+    fn visit_grouping_expr(&mut self, expr: &Grouping) -> String {
+        format!("({})", self.visit_expr(&expr.expression))
+    }
+
+    fn visit_interpolation_expr(&mut self, expr: &Interpolation) -> String {
+        let mut retval = String::from("\"");
+
+        for (index, part) in expr.parts.iter().enumerate() {
+            match part {
+                Expr::Literal(literal) if index % 2 == 0 => {
+                    if let Object::String(segment) = &literal.value {
+                        retval.push_str(&escape(segment));
+                    }
+                }
+                part => retval.push_str(&format!("${{{}}}", self.visit_expr(part))),
+            }
+        }
+
+        retval.push('"');
+        retval
+    }
+
+    fn visit_literal_expr(&mut self, expr: &Literal) -> String {
+        match &expr.value {
+            Object::String(value) => format!("\"{}\"", escape(value)),
+            // Keeps the `.0` of whole numbers, so that they are scanned as floats again.
+            Object::Number(value) => format!("{:?}", value),
+            value => value.to_string(),
+        }
+    }
+
+    fn visit_unary_expr(&mut self, expr: &Unary) -> String {
+        format!("{}{}", expr.operator.lexeme, self.visit_expr(&expr.right))
+    }
+
+    fn visit_variable_expr(&mut self, expr: &Variable) -> String {
+        expr.name.lexeme.clone()
+    }
+}
+
+impl StmtVisitor<String> for SourcePrinter {
+    fn visit_stmt(&mut self, stmt: &Stmt) -> String {
+        match stmt {
+            Stmt::Expression(stmt) => self.visit_expression_stmt(stmt),
+            Stmt::Print(stmt) => self.visit_print_stmt(stmt),
+            Stmt::Var(stmt) => self.visit_var_stmt(stmt),
+            Stmt::Empty => String::new(),
+        }
+    }
+
+    fn visit_expression_stmt(&mut self, stmt: &Expression) -> String {
+        format!("{};", self.visit_expr(&stmt.expr))
+    }
+
+    fn visit_print_stmt(&mut self, stmt: &Print) -> String {
+        format!("print {};", self.visit_expr(&stmt.expr))
+    }
+
+    fn visit_var_stmt(&mut self, stmt: &Var) -> String {
+        match &stmt.initializer {
+            Some(initializer) => {
+                format!(
+                    "var {} = {};",
+                    stmt.name.lexeme,
+                    self.visit_expr(initializer)
+                )
+            }
+            None => format!("var {};", stmt.name.lexeme),
+        }
+    }
+}
+
+/// Escapes a string's value to be written between quotes.
+fn escape(value: &str) -> String {
+    let mut retval = String::new();
+
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            // Would start an interpolation.
+            '$' if chars.peek() == Some(&'{') => retval.push_str("\\$"),
+            '\n' => retval.push_str("\\n"),
+            '\t' => retval.push_str("\\t"),
+            '\\' => retval.push_str("\\\\"),
+            '"' => retval.push_str("\\\""),
+            c if c.is_control() => retval.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => retval.push(c),
+        }
+    }
+
+    retval
+}
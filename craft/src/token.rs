@@ -3,6 +3,7 @@ use std::fmt::Display;
 pub use crate::token_type::TokenType;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Token {
     pub variant: TokenType,
     pub lexeme: String,
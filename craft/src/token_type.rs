@@ -1,5 +1,9 @@
+#[cfg(feature = "bigint")]
+use crate::bigint::BigInt;
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TokenType {
     // Single-character tokens.
     LEFT_PAREN,
@@ -13,6 +17,13 @@ pub enum TokenType {
     SEMICOLON,
     SLASH,
     STAR,
+    PERCENT,
+    AMPERSAND,
+    PIPE,
+    CARET,
+    TILDE,
+    QUESTION,
+    COLON,
 
     // One or two character tokens.
     BANG,
@@ -23,11 +34,21 @@ pub enum TokenType {
     GREATER_EQUAL,
     LESS,
     LESS_EQUAL,
+    LESS_LESS,
+    GREATER_GREATER,
+    STAR_STAR,
+    TILDE_SLASH,
 
     // Literals.
     IDENTIFIER(String),
     STRING(String),
+    /// The part of an interpolated string before a `${`, holding the decoded segment.
+    INTERPOLATION(String),
     NUMBER(f64),
+    INTEGER(i64),
+    /// An integer literal too large for `INTEGER`.
+    #[cfg(feature = "bigint")]
+    BIG_INTEGER(BigInt),
 
     // Keywords.
     AND,
@@ -47,5 +68,8 @@ pub enum TokenType {
     VAR,
     WHILE,
 
+    // Trivia, only scanned for the formatter.
+    COMMENT,
+
     EOF,
 }
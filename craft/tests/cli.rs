@@ -0,0 +1,336 @@
+mod common;
+
+use common::script;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+/// Runs the interpreter binary with the given arguments, keeping its log file out of the real
+/// home directory.
+fn craft(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_craft"))
+        .args(args)
+        .env("HOME", env!("CARGO_TARGET_TMPDIR"))
+        .output()
+        .expect("Failed to run craft")
+}
+
+fn run_script(name: &str) -> Output {
+    craft(&["--file", script(name).to_str().unwrap()])
+}
+
+#[test]
+fn successful_script_exits_with_zero() {
+    let output = run_script("print/literals.lox");
+
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("hello\n"));
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn compile_error_exits_with_65() {
+    let output = run_script("expressions/missing_semicolon.lox");
+
+    assert_eq!(output.status.code(), Some(65));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "[line 2] Error at end: Expect ';' after expression.\n"
+    );
+}
+
+#[test]
+fn runtime_error_exits_with_70() {
+    let output = run_script("variable/undefined_global.lox");
+
+    assert_eq!(output.status.code(), Some(70));
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("Undefined variable 'notDefined'.\n[line 1]"));
+}
+
+#[test]
+fn unknown_argument_exits_with_64() {
+    let output = craft(&["--no-such-flag"]);
+
+    assert_eq!(output.status.code(), Some(64));
+}
+
+#[test]
+fn help_exits_with_zero() {
+    let output = craft(&["--help"]);
+
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn missing_file_exits_with_66() {
+    let output = run_script("does/not/exist.lox");
+
+    assert_eq!(output.status.code(), Some(66));
+}
+
+/// Runs the interpreter binary with its output going to a device that is always full.
+#[cfg(target_os = "linux")]
+fn craft_to_full_device(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_craft"))
+        .args(args)
+        .env("HOME", env!("CARGO_TARGET_TMPDIR"))
+        .stdout(std::fs::File::create("/dev/full").unwrap())
+        .output()
+        .expect("Failed to run craft")
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn write_failure_exits_with_74() {
+    let path = script("print/literals.lox");
+    let output = craft_to_full_device(&["--tokens", "--file", path.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(74));
+
+    let output = craft_to_full_device(&["--file", path.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(74));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No space left on device"));
+}
+
+/// Runs the REPL with the given input piped to it.
+fn repl(input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_craft"))
+        .env("HOME", env!("CARGO_TARGET_TMPDIR"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to run craft");
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn repl_keeps_state_between_lines() {
+    let output = repl("var a = 1;\nprint nope;\n\nprint a;\na + 2\n");
+
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.lines().any(|line| line == "1"));
+    assert!(stdout.lines().any(|line| line == "3"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Undefined variable 'nope'."));
+}
+
+#[test]
+fn repl_continues_unbalanced_input() {
+    let output = repl("print (1 +\n2);\nprint \"a\nb\";\n");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("\n3\na\nb\n"));
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("Error"));
+}
+
+#[test]
+fn repl_continues_escaped_and_raw_strings() {
+    let output = repl("print \"a\\\"\nb\";\nprint r#\"c\"\nd\"#;\n");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("a\"\nb\n"));
+    assert!(stdout.contains("c\"\nd\n"));
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("Error"));
+}
+
+#[test]
+fn repl_continues_block_comments() {
+    let output = repl("/* a /* nested */\nprint 1; */ print 2;\n");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.lines().any(|line| line == "2"));
+    assert!(!stdout.lines().any(|line| line == "1"));
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("Error"));
+}
+
+#[test]
+fn repl_handles_brackets_inside_interpolation() {
+    // The error on the second line only stops it if the first line was taken as complete.
+    let output = repl("print \"${\"{\"}\";\nprint nope;\nprint \"${(\n1)}\";\n");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.lines().any(|line| line.ends_with('{')));
+    assert!(stdout.lines().any(|line| line.ends_with('1')));
+}
+
+#[test]
+fn repl_meta_commands() {
+    let load = format!(":load {}\n", script("print/literals.lox").display());
+    let output = repl(
+        &(String::from(
+            "var a = 1;\n:env\n:tokens a + 1\n:ast print -a * 2;\n:reset\n:env\n:unknown\n",
+        ) + &load),
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(stdout.contains("a = 1\nclock = <native fn clock>\nfloat = <native fn float>\n"));
+    assert!(stdout.contains("IDENTIFIER(\"a\") a\nPLUS +\nINTEGER(1) 1\nEOF ~EOF\n"));
+    assert!(stdout.contains("(print (* (- a) 2))\n"));
+    assert!(stdout.contains("\nint = <native fn int>\n123\nhello\n"));
+    assert!(stderr.contains("Unknown command :unknown."));
+}
+
+#[test]
+fn tokens_dump() {
+    let output = craft(&[
+        "--tokens",
+        "--file",
+        script("print/literals.lox").to_str().unwrap(),
+    ]);
+
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stdout)
+        .starts_with("PRINT print\nINTEGER(123) 123\nSEMICOLON ;\n"));
+}
+
+#[test]
+fn ast_dump() {
+    let output = craft(&[
+        "--ast",
+        "--file",
+        script("expressions/evaluate.lox").to_str().unwrap(),
+    ]);
+
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stdout)
+        .starts_with("(print (+ (group (- 5 (group (- 3 1)))) (- 1)))\n"));
+}
+
+#[test]
+fn ast_dump_reports_compile_errors() {
+    let output = craft(&[
+        "--ast",
+        "--file",
+        script("expressions/missing_semicolon.lox")
+            .to_str()
+            .unwrap(),
+    ]);
+
+    assert_eq!(output.status.code(), Some(65));
+    assert!(output.stdout.is_empty());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn json_dumps() {
+    let output = craft(&[
+        "--tokens",
+        "--json",
+        "--file",
+        script("print/literals.lox").to_str().unwrap(),
+    ]);
+
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with('['));
+    assert!(stdout.contains("\"variant\": {\n      \"INTEGER\": 123\n    }"));
+
+    let output = craft(&[
+        "--ast",
+        "--json",
+        "--file",
+        script("print/literals.lox").to_str().unwrap(),
+    ]);
+
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("\"Print\""));
+    assert!(stdout.contains("\"String\": \"hello\""));
+}
+
+#[cfg(not(feature = "serde"))]
+#[test]
+fn json_needs_the_serde_feature() {
+    let output = craft(&[
+        "--tokens",
+        "--json",
+        "--file",
+        script("print/literals.lox").to_str().unwrap(),
+    ]);
+
+    assert_eq!(output.status.code(), Some(70));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("requires the `serde` feature"));
+}
+
+#[test]
+fn json_requires_a_dump_mode() {
+    let output = craft(&[
+        "--json",
+        "--file",
+        script("print/literals.lox").to_str().unwrap(),
+    ]);
+
+    assert_eq!(output.status.code(), Some(64));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn runs_ast_saved_as_json() {
+    let output = craft(&[
+        "--ast",
+        "--json",
+        "--file",
+        script("variable/define_and_assign.lox").to_str().unwrap(),
+    ]);
+    assert_eq!(output.status.code(), Some(0));
+
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("define_and_assign.json");
+    std::fs::write(&path, &output.stdout).unwrap();
+
+    let output = craft(&["--from-ast", "--file", path.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "before\nafter\narg\narg\n"
+    );
+
+    let output = craft(&[
+        "--from-ast",
+        "--file",
+        script("print/literals.lox").to_str().unwrap(),
+    ]);
+    assert_eq!(output.status.code(), Some(65));
+}
+
+#[test]
+fn fmt_check_and_rewrite() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("unformatted.lox");
+    std::fs::write(&path, "print  1+2 ; // Three.\n").unwrap();
+    let path = path.to_str().unwrap();
+
+    let output = craft(&["fmt", "--check", path]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Would reformat"));
+    assert_eq!(
+        std::fs::read_to_string(path).unwrap(),
+        "print  1+2 ; // Three.\n"
+    );
+
+    let output = craft(&["fmt", path]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        std::fs::read_to_string(path).unwrap(),
+        "print 1 + 2; // Three.\n"
+    );
+
+    let output = craft(&["fmt", "--check", path]);
+    assert_eq!(output.status.code(), Some(0));
+
+    let output = craft(&[
+        "fmt",
+        script("expressions/missing_semicolon.lox")
+            .to_str()
+            .unwrap(),
+    ]);
+    assert_eq!(output.status.code(), Some(65));
+}
@@ -0,0 +1,64 @@
+//! Helpers shared by the integration tests.
+
+// Each test crate uses only some of the helpers.
+#![allow(dead_code)]
+
+use craft::output::CapturedOutput;
+use craft::Engine;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Creates an engine whose output and error messages are captured.
+pub fn engine() -> (Engine, CapturedOutput, CapturedOutput) {
+    let stdout = CapturedOutput::new();
+    let stderr = CapturedOutput::new();
+
+    let mut engine = Engine::new();
+    engine.set_stdout(stdout.clone());
+    engine.set_stderr(stderr.clone());
+
+    (engine, stdout, stderr)
+}
+
+/// Creates an engine that keeps error messages out of the test output.
+pub fn quiet_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.set_stderr(CapturedOutput::new());
+    engine
+}
+
+/// Returns the directory holding the golden `.lox` scripts.
+pub fn scripts_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("lox")
+}
+
+/// Returns the path of the golden script with the given name, relative to [`scripts_dir`].
+pub fn script(name: &str) -> PathBuf {
+    scripts_dir().join(name)
+}
+
+/// Returns every golden script in order, leaving out the ones for features that aren't enabled.
+pub fn golden_scripts() -> Vec<PathBuf> {
+    let mut scripts = Vec::new();
+    collect_scripts(&scripts_dir(), &mut scripts);
+    scripts.sort();
+    scripts
+}
+
+fn collect_scripts(dir: &Path, scripts: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).expect("Failed to read test directory") {
+        let path = entry.expect("Failed to read directory entry").path();
+
+        if path.is_dir() {
+            // Scripts for big integers only pass when the feature is enabled.
+            if path.ends_with("bigint") && !cfg!(feature = "bigint") {
+                continue;
+            }
+            collect_scripts(&path, scripts);
+        } else if path.extension().is_some_and(|ext| ext == "lox") {
+            scripts.push(path);
+        }
+    }
+}
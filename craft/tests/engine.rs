@@ -0,0 +1,171 @@
+mod common;
+
+use common::engine;
+use craft::ast::Object;
+use craft::interpreter::RuntimeError;
+use craft::output::CapturedOutput;
+use craft::Error;
+
+#[test]
+fn eval_returns_value_of_trailing_expression() {
+    let (mut engine, stdout, _) = engine();
+
+    let value = engine.eval("1 + 2;").unwrap();
+    assert!(matches!(value, Object::Int(3)));
+    assert_eq!(stdout.contents(), "");
+
+    let value = engine.eval("print 1;").unwrap();
+    assert!(matches!(value, Object::Nil));
+    assert_eq!(stdout.contents(), "1\n");
+}
+
+#[test]
+fn eval_repl_prints_bare_expressions() {
+    let (mut engine, stdout, _) = engine();
+
+    engine.eval_repl("var a = 2;").unwrap();
+    engine.eval_repl("a * 3").unwrap();
+    engine.eval_repl("a = 4;").unwrap();
+    assert_eq!(stdout.contents(), "6\n");
+
+    // Scripts still require the semicolon.
+    assert!(matches!(engine.eval("a * 3"), Err(Error::Compile(_))));
+}
+
+#[test]
+fn globals_survive_between_evals() {
+    let (mut engine, stdout, _) = engine();
+
+    engine.eval("var a = 1;").unwrap();
+    engine.eval("a = a + 1;").unwrap();
+    engine.eval("print a;").unwrap();
+
+    assert!(matches!(engine.get_global("a"), Some(Object::Int(2))));
+    assert!(engine.get_global("b").is_none());
+    assert_eq!(stdout.contents(), "2\n");
+}
+
+#[test]
+fn set_global_is_visible_to_scripts() {
+    let (mut engine, stdout, _) = engine();
+
+    engine.set_global("name", Object::String("craft".to_string()));
+    engine.eval("print name + \"!\";").unwrap();
+
+    assert_eq!(stdout.contents(), "craft!\n");
+}
+
+#[test]
+fn call_function_invokes_natives() {
+    let (mut engine, _, stderr) = engine();
+    engine.define_native("double", 1, |args| match &args[0] {
+        Object::Number(n) => Ok(Object::Number(n * 2.0)),
+        _ => Err(RuntimeError::message("Expected a number.")),
+    });
+
+    let value = engine
+        .call_function("double", &[Object::Number(21.0)])
+        .unwrap();
+    assert!(matches!(value, Object::Number(n) if n == 42.0));
+
+    let err = engine.call_function("double", &[Object::Nil]).unwrap_err();
+    assert!(matches!(err, Error::Runtime(err) if err.message == "Expected a number."));
+    assert_eq!(stderr.take(), "Expected a number.\n");
+
+    let err = engine.call_function("missing", &[]).unwrap_err();
+    assert!(matches!(err, Error::UndefinedGlobal(name) if name == "missing"));
+}
+
+#[test]
+fn compile_errors_are_reported() {
+    let (mut engine, stdout, stderr) = engine();
+
+    let err = engine.eval("print 1").unwrap_err();
+    assert!(matches!(err, Error::Compile(messages) if messages.len() == 1));
+    assert_eq!(stdout.contents(), "");
+    assert!(stderr.contents().starts_with("[line 1] Error"));
+}
+
+#[test]
+fn runtime_errors_report_the_call_site() {
+    let (mut engine, stdout, stderr) = engine();
+
+    let err = engine.eval("print 1;\n\nclock(1);\nprint 2;").unwrap_err();
+    let Error::Runtime(err) = err else {
+        panic!("expected a runtime error");
+    };
+
+    assert_eq!(err.message, "Expected 0 arguments but got 1.");
+    assert_eq!(err.token.unwrap().line, 3);
+    assert_eq!(stdout.contents(), "1\n");
+    assert_eq!(
+        stderr.contents(),
+        "Expected 0 arguments but got 1.\n[line 3]\n"
+    );
+}
+
+#[test]
+fn run_with_output_captures_print() {
+    let stdout = CapturedOutput::new();
+    let stderr = CapturedOutput::new();
+
+    craft::run_with_output("print \"hi\";\nprint nope;", stdout.clone(), stderr.clone())
+        .unwrap_err();
+
+    assert_eq!(stdout.contents(), "hi\n");
+    assert_eq!(stderr.contents(), "Undefined variable 'nope'.\n[line 2]\n");
+}
+
+#[test]
+fn strings_hold_decoded_escapes() {
+    let (mut engine, _, _) = engine();
+
+    let value = engine.eval(r#""\u{1F600}\t\"\\";"#).unwrap();
+    assert!(matches!(value, Object::String(s) if s == "😀\t\"\\"));
+
+    let value = engine.eval(r##"r#"\n"#;"##).unwrap();
+    assert!(matches!(value, Object::String(s) if s == "\\n"));
+}
+
+#[cfg(feature = "bigint")]
+#[test]
+fn integers_grow_beyond_64_bits() {
+    let (mut engine, _, _) = engine();
+
+    let value = engine.eval("9223372036854775807 + 1;").unwrap();
+    assert!(matches!(value, Object::BigInt(n) if n.to_string() == "9223372036854775808"));
+
+    let value = engine.eval("(9223372036854775807 + 1) - 1;").unwrap();
+    assert!(matches!(value, Object::Int(i64::MAX)));
+}
+
+#[cfg(not(feature = "bigint"))]
+#[test]
+fn integers_overflow_without_big_integers() {
+    let (mut engine, _, _) = engine();
+
+    let err = engine.eval("9223372036854775807 + 1;").unwrap_err();
+    assert!(matches!(err, Error::Runtime(err) if err.message == "Integer overflow."));
+
+    let err = engine.eval("-(-9223372036854775807 - 1);").unwrap_err();
+    assert!(matches!(err, Error::Runtime(err) if err.message == "Integer overflow."));
+
+    let err = engine.eval("1 << 63;").unwrap_err();
+    assert!(matches!(err, Error::Runtime(err) if err.message == "Integer overflow."));
+
+    let value = engine.eval("-1 << 63;").unwrap();
+    assert!(matches!(value, Object::Int(i64::MIN)));
+
+    let value = engine.eval("(-1) ** 5000000001;").unwrap();
+    assert!(matches!(value, Object::Int(-1)));
+
+    let err = engine.eval("1 << 64;").unwrap_err();
+    assert!(
+        matches!(err, Error::Runtime(err) if err.message == "Shift amount must be less than 64.")
+    );
+
+    let err = engine.eval("9223372036854775808;").unwrap_err();
+    assert!(
+        matches!(err, Error::Compile(messages) if messages[0].ends_with("Number literal is too large."))
+    );
+}
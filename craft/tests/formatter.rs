@@ -0,0 +1,144 @@
+mod common;
+
+use common::quiet_engine;
+use craft::ast_printer::print_stmt;
+use craft::Error;
+use std::fs;
+
+#[test]
+fn normalizes_spacing_and_keeps_comments() {
+    let source = "\
+// Header.
+var  a=1 ; // One.
+
+
+
+var b;
+print(a+ -b)*2 ;
+print a +  // Middle.
+  b;
+  print !a;
+clock( 1,a=\"x\" ) ;   ";
+
+    assert_eq!(
+        quiet_engine().format(source).unwrap(),
+        "\
+// Header.
+var a = 1; // One.
+
+var b;
+print (a + -b) * 2;
+print a + // Middle.
+    b;
+print !a;
+clock(1, a = \"x\");
+"
+    );
+}
+
+#[test]
+fn does_not_format_invalid_code() {
+    assert!(matches!(
+        quiet_engine().format("print 1"),
+        Err(Error::Compile(_))
+    ));
+}
+
+#[test]
+fn golden_scripts_keep_their_meaning() {
+    let mut checked = 0;
+    for script in &common::golden_scripts() {
+        let source = fs::read_to_string(script).expect("Failed to read test script");
+        // Scripts with compile errors can't be formatted.
+        let Ok(formatted) = quiet_engine().format(&source) else {
+            continue;
+        };
+
+        let tree: Vec<String> = quiet_engine()
+            .parse(&source)
+            .unwrap()
+            .iter()
+            .map(print_stmt)
+            .collect();
+        let formatted_tree: Vec<String> = quiet_engine()
+            .parse(&formatted)
+            .unwrap()
+            .iter()
+            .map(print_stmt)
+            .collect();
+
+        assert_eq!(tree, formatted_tree, "{:?}", script);
+        assert_eq!(
+            source.matches("//").count(),
+            formatted.matches("//").count(),
+            "{:?}",
+            script
+        );
+        assert_eq!(
+            formatted,
+            quiet_engine().format(&formatted).unwrap(),
+            "{:?}",
+            script
+        );
+        checked += 1;
+    }
+
+    assert!(checked > 0, "No test scripts formatted");
+}
+
+#[test]
+fn keeps_block_comments_in_place() {
+    let source = "\
+/* Header
+   over two lines. */
+print  1 /* one */+2; /* After. */
+/* Before. */ print 3;
+print -/* minus */1;";
+    let formatted = quiet_engine().format(source).unwrap();
+
+    assert_eq!(
+        formatted,
+        "\
+/* Header
+   over two lines. */
+print 1 /* one */ + 2; /* After. */
+/* Before. */ print 3;
+print - /* minus */ 1;
+"
+    );
+    assert_eq!(quiet_engine().format(&formatted).unwrap(), formatted);
+}
+
+#[test]
+fn keeps_semicolons_before_comments() {
+    let source = "\
+print 1 // One.
+;
+print 2 /* Two. */
+  ;
+print 3
+// Three.
+;";
+    let formatted = quiet_engine().format(source).unwrap();
+
+    assert_eq!(
+        formatted,
+        "\
+print 1; // One.
+print 2; /* Two. */
+print 3;
+// Three.
+"
+    );
+    assert_eq!(quiet_engine().format(&formatted).unwrap(), formatted);
+}
+
+#[test]
+fn formats_interpolated_expressions() {
+    assert_eq!(
+        quiet_engine()
+            .format("print  \"a${ 1+2 }b${\"c${ x }\"}\" ;")
+            .unwrap(),
+        "print \"a${1 + 2}b${\"c${x}\"}\";\n"
+    );
+}
@@ -0,0 +1,141 @@
+//! Runs every `.lox` script under `tests/lox` and checks its output against the expectations
+//! written in its comments, following the conventions of the Crafting Interpreters test suite:
+//!
+//! - `// expect: <text>` - the script prints `<text>` on its own line.
+//! - `// expect runtime error: <message>` - the script fails with this runtime error, reported
+//!   on the line of the comment.
+//! - `// [line N] Error ...` - a compile error is reported on line `N`.
+//! - `// Error ...` - a compile error is reported on the line of the comment.
+//!
+//! Scripts exit with 65 when they have compile errors, 70 on runtime errors and 0 otherwise.
+
+mod common;
+
+use craft::output::CapturedOutput;
+use std::fs;
+use std::path::Path;
+
+const EXIT_COMPILE_ERROR: i32 = 65;
+const EXIT_RUNTIME_ERROR: i32 = 70;
+
+/// What a script is expected to do, parsed from its comments.
+#[derive(Default)]
+struct Expectations {
+    output: Vec<String>,
+    errors: Vec<String>,
+    exit_code: i32,
+}
+
+impl Expectations {
+    fn parse(source: &str) -> Self {
+        let mut expectations = Expectations::default();
+
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index + 1;
+            let Some(comment) = line.find("//").map(|start| &line[start + 2..]) else {
+                continue;
+            };
+            let comment = comment.trim_start();
+
+            if let Some(text) = comment.strip_prefix("expect: ") {
+                expectations.output.push(text.to_string());
+            } else if let Some(message) = comment.strip_prefix("expect runtime error: ") {
+                expectations.errors.push(message.to_string());
+                expectations.errors.push(format!("[line {}]", line_number));
+                expectations.exit_code = EXIT_RUNTIME_ERROR;
+            } else if comment.starts_with("[line ") && comment.contains("] Error") {
+                expectations.errors.push(comment.to_string());
+                expectations.exit_code = EXIT_COMPILE_ERROR;
+            } else if comment.starts_with("Error") {
+                expectations
+                    .errors
+                    .push(format!("[line {}] {}", line_number, comment));
+                expectations.exit_code = EXIT_COMPILE_ERROR;
+            }
+        }
+
+        expectations
+    }
+}
+
+/// Runs a single script, returning a description of each mismatch.
+fn run_script(path: &Path) -> Vec<String> {
+    let source = fs::read_to_string(path).expect("Failed to read test script");
+    let expected = Expectations::parse(&source);
+
+    let stdout = CapturedOutput::new();
+    let stderr = CapturedOutput::new();
+    let result = craft::run_with_output(&source, stdout.clone(), stderr.clone());
+
+    let exit_code = match result {
+        Ok(()) => 0,
+        Err(err) => match err.downcast_ref::<craft::Error>() {
+            Some(craft::Error::Compile(_)) => EXIT_COMPILE_ERROR,
+            _ => EXIT_RUNTIME_ERROR,
+        },
+    };
+
+    let mut failures = Vec::new();
+    compare_lines(
+        "output",
+        &expected.output,
+        &stdout.contents(),
+        &mut failures,
+    );
+    compare_lines("error", &expected.errors, &stderr.contents(), &mut failures);
+
+    if exit_code != expected.exit_code {
+        failures.push(format!(
+            "Expected exit code {} but got {}.",
+            expected.exit_code, exit_code
+        ));
+    }
+
+    failures
+}
+
+fn compare_lines(kind: &str, expected: &[String], actual: &str, failures: &mut Vec<String>) {
+    let actual: Vec<&str> = actual.lines().collect();
+
+    for (index, expected_line) in expected.iter().enumerate() {
+        match actual.get(index) {
+            Some(actual_line) if actual_line == expected_line => (),
+            Some(actual_line) => failures.push(format!(
+                "Expected {} '{}' on line {} and got '{}'.",
+                kind,
+                expected_line,
+                index + 1,
+                actual_line
+            )),
+            None => failures.push(format!("Missing expected {} '{}'.", kind, expected_line)),
+        }
+    }
+
+    for actual_line in actual.iter().skip(expected.len()) {
+        failures.push(format!("Got unexpected {} '{}'.", kind, actual_line));
+    }
+}
+
+#[test]
+fn golden_scripts() {
+    let root = common::scripts_dir();
+    let scripts = common::golden_scripts();
+
+    assert!(!scripts.is_empty(), "No test scripts found in {:?}", root);
+
+    let mut report = String::new();
+    for script in &scripts {
+        let failures = run_script(script);
+        if failures.is_empty() {
+            continue;
+        }
+
+        let name = script.strip_prefix(&root).unwrap_or(script);
+        report.push_str(&format!("FAIL {}\n", name.display()));
+        for failure in failures {
+            report.push_str(&format!("     {}\n", failure));
+        }
+    }
+
+    assert!(report.is_empty(), "\n{}", report);
+}
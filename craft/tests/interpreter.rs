@@ -0,0 +1,26 @@
+use craft::ast::Object;
+use craft::interpreter::{Interpreter, RuntimeError};
+use craft::output::CapturedOutput;
+use craft::Engine;
+
+#[test]
+fn natives_defined_on_the_interpreter_are_callable() {
+    let stdout = CapturedOutput::new();
+    let mut interpreter =
+        Interpreter::with_output(Box::new(stdout.clone()), Box::new(CapturedOutput::new()));
+    interpreter.define_native("join", 2, |args| match (&args[0], &args[1]) {
+        (Object::String(a), Object::String(b)) => Ok(Object::String(format!("{}-{}", a, b))),
+        _ => Err(RuntimeError::message("Expected strings.")),
+    });
+
+    let stmts = Engine::new()
+        .parse("print join(\"a\", \"b\");\nprint clock() > 0;")
+        .unwrap();
+    interpreter.interpret(stmts).unwrap();
+    assert_eq!(stdout.contents(), "a-b\ntrue\n");
+
+    let stmts = Engine::new().parse("join(\"a\");").unwrap();
+    let err = interpreter.interpret(stmts).unwrap_err();
+    assert_eq!(err.message, "Expected 2 arguments but got 1.");
+    assert_eq!(err.token.unwrap().line, 1);
+}
@@ -0,0 +1,58 @@
+use craft::repl::ReplHelper;
+
+fn complete(line: &str) -> (usize, Vec<String>) {
+    let helper = ReplHelper {
+        globals: vec!["counter".to_string(), "clock".to_string()],
+    };
+    helper.candidates(line, line.len())
+}
+
+#[test]
+fn completes_keywords_and_globals() {
+    assert_eq!(
+        complete("print cl"),
+        (6, vec!["class".to_string(), "clock".to_string()])
+    );
+    assert_eq!(complete("var x = co"), (8, vec!["counter".to_string()]));
+    assert_eq!(complete("wh"), (0, vec!["while".to_string()]));
+}
+
+#[test]
+fn completes_commands() {
+    assert_eq!(
+        complete(":t"),
+        (1, vec!["time".to_string(), "tokens".to_string()])
+    );
+}
+
+#[test]
+fn does_not_complete_empty_input_or_properties() {
+    assert_eq!(complete(""), (0, vec![]));
+    assert_eq!(complete("counter.c"), (8, vec![]));
+}
+
+#[test]
+fn completes_unicode_names() {
+    let helper = ReplHelper {
+        globals: vec!["café".to_string(), "名前".to_string()],
+    };
+
+    assert_eq!(
+        helper.candidates("print ca", 8),
+        (6, vec!["café".to_string()])
+    );
+    let line = "x + 名";
+    assert_eq!(
+        helper.candidates(line, line.len()),
+        (4, vec!["名前".to_string()])
+    );
+}
+
+#[test]
+fn completes_after_multibyte_punctuation() {
+    assert_eq!(
+        complete("print \"→cl"),
+        (10, vec!["class".to_string(), "clock".to_string()])
+    );
+    assert_eq!(complete("print «").0, "print «".len());
+}
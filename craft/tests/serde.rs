@@ -0,0 +1,83 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+use craft::ast::Stmt;
+
+const SOURCE: &str = "
+var greeting = \"hello\";
+greeting = greeting + \" world\";
+print greeting;
+print -(1 + 2) * 3 == -9;
+print clock() > 0;
+";
+
+#[test]
+fn ast_round_trips_through_json() {
+    let (mut engine, expected, _) = common::engine();
+    let stmts = engine.parse(SOURCE).unwrap();
+    engine.execute(&stmts).unwrap();
+
+    let json = serde_json::to_string(&stmts).unwrap();
+    let loaded: Vec<Stmt> = craft::stmts_from_json(&json).unwrap();
+
+    // Serializing the loaded tree again gives the same JSON.
+    assert_eq!(serde_json::to_string(&loaded).unwrap(), json);
+
+    let (mut engine, actual, _) = common::engine();
+    engine.execute(&loaded).unwrap();
+    assert_eq!(actual.contents(), expected.contents());
+    assert_eq!(actual.contents(), "hello world\ntrue\ntrue\n");
+}
+
+#[test]
+fn tokens_round_trip_through_json() {
+    let (mut engine, _, _) = common::engine();
+    let tokens = engine.scan(SOURCE).unwrap();
+
+    let json = serde_json::to_string(&tokens).unwrap();
+    let loaded: Vec<craft::token::Token> = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(loaded.len(), tokens.len());
+    for (loaded, token) in loaded.iter().zip(&tokens) {
+        assert_eq!(loaded.variant, token.variant);
+        assert_eq!(loaded.lexeme, token.lexeme);
+        assert_eq!(loaded.line, token.line);
+    }
+}
+
+#[test]
+fn invalid_json_is_rejected() {
+    assert!(craft::stmts_from_json("[{\"Print\": 1}]").is_err());
+}
+
+#[test]
+fn loaded_operators_the_parser_never_builds_are_runtime_errors() {
+    let (mut engine, _, _) = common::engine();
+    let json = serde_json::to_string(&engine.parse("print 1 + 2;").unwrap()).unwrap();
+    let loaded = craft::stmts_from_json(&json.replace("\"PLUS\"", "\"DOT\"")).unwrap();
+
+    let err = engine.execute(&loaded).unwrap_err();
+    assert!(
+        matches!(err, craft::Error::Runtime(err) if err.message == "Invalid operator for integers.")
+    );
+}
+
+#[cfg(feature = "bigint")]
+#[test]
+fn loaded_big_integer_zero_is_falsey() {
+    let (mut engine, stdout, _) = common::engine();
+    let stmts = engine
+        .parse("print 18446744073709551616 ? \"yes\" : \"no\";")
+        .unwrap();
+
+    let json = serde_json::to_string(&stmts).unwrap();
+    let big: craft::bigint::BigInt = "18446744073709551616".parse().unwrap();
+    let big = serde_json::to_string(&big).unwrap();
+    let zero = serde_json::to_string(&craft::bigint::BigInt::from(0)).unwrap();
+    assert!(json.contains(&big));
+    let loaded = craft::stmts_from_json(&json.replace(&big, &zero)).unwrap();
+
+    engine.execute(&loaded).unwrap();
+    assert_eq!(stdout.contents(), "no\n");
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use craft::ast::Stmt;
+use craft::ast_printer::print_stmt;
+use craft::source_printer::print_source;
+use std::fs;
+
+/// Parses the source with an engine that keeps error messages out of the test output.
+fn parse(source: &str) -> Option<Vec<Stmt>> {
+    common::quiet_engine().parse(source).ok()
+}
+
+/// Prints the tree as S-expressions, which show its full structure.
+fn tree(stmts: &[Stmt]) -> Vec<String> {
+    stmts.iter().map(print_stmt).collect()
+}
+
+/// Prints the tree with its literal values' types, which the S-expressions leave out, but
+/// without line numbers, which the printed source doesn't keep.
+fn debug_tree(stmts: &[Stmt]) -> String {
+    let debug = format!("{:?}", stmts);
+    let mut rest = debug.as_str();
+    let mut tree = String::new();
+    while let Some(start) = rest.find("line: ") {
+        tree.push_str(&rest[..start]);
+        rest = rest[start + "line: ".len()..].trim_start_matches(|c: char| c.is_ascii_digit());
+    }
+    tree.push_str(rest);
+    tree
+}
+
+#[test]
+fn prints_canonical_source() {
+    let stmts = parse("var  a=1 ;var b;\n\nprint(a+ -b)*2 ;\nclock( 1,a=\"x\" ) ;").unwrap();
+
+    assert_eq!(
+        print_source(&stmts),
+        "var a = 1;\nvar b;\nprint (a + -b) * 2;\nclock(1, a = \"x\");\n"
+    );
+}
+
+#[test]
+fn escapes_strings() {
+    let stmts = parse(r#"print "tab\t\"quoted\" \\ \u{7}"; print r"raw\n";"#).unwrap();
+
+    assert_eq!(
+        print_source(&stmts),
+        concat!(
+            r#"print "tab\t\"quoted\" \\ \u{7}";"#,
+            "\n",
+            r#"print "raw\\n";"#,
+            "\n"
+        )
+    );
+}
+
+#[test]
+fn prints_interpolated_strings() {
+    let stmts = parse(r#"print "a${1+2}b${"c${x}\${"}";"#).unwrap();
+
+    assert_eq!(
+        print_source(&stmts),
+        concat!(r#"print "a${1 + 2}b${"c${x}\${"}";"#, "\n")
+    );
+}
+
+#[test]
+fn prints_conditional_and_comma_expressions() {
+    let stmts = parse("a?b:c?d:e ; f((1,2) ,3) ;").unwrap();
+
+    let tree = tree(&stmts);
+    assert_eq!(tree[0], "(; (?: a b (?: c d e)))");
+    assert_eq!(tree[1], "(; (call f (group (, 1 2)) 3))");
+    assert_eq!(print_source(&stmts), "a ? b : c ? d : e;\nf((1, 2), 3);\n");
+}
+
+#[test]
+fn golden_scripts_round_trip() {
+    let mut checked = 0;
+    for script in &common::golden_scripts() {
+        let source = fs::read_to_string(script).expect("Failed to read test script");
+        // Scripts with compile errors have no tree to print.
+        let Some(stmts) = parse(&source) else {
+            continue;
+        };
+
+        let printed = print_source(&stmts);
+        let reparsed = parse(&printed)
+            .unwrap_or_else(|| panic!("Printed {:?} doesn't parse:\n{}", script, printed));
+
+        assert_eq!(tree(&stmts), tree(&reparsed), "{:?}", script);
+        assert_eq!(debug_tree(&stmts), debug_tree(&reparsed), "{:?}", script);
+        assert_eq!(printed, print_source(&reparsed), "{:?}", script);
+        checked += 1;
+    }
+
+    assert!(checked > 0, "No test scripts parsed");
+}